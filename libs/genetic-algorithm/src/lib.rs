@@ -1,11 +1,20 @@
 use rand::seq::SliceRandom;
 use rand::{Rng, RngCore};
 use std::ops::Index;
+use thiserror::Error;
 
 pub struct GeneticAlgorithm<S> {
     selection_method: S,
     crossover_method: Box<dyn CrossoverMethod>,
     mutation_method: Box<dyn MutationMethod>,
+
+    /// Mating pool reused by [`Self::evolve_into()`], see
+    /// [`SelectionMethod::pools()`]
+    pool: Vec<usize>,
+
+    /// Chromosomes of the individuals dropped by [`Self::evolve_into()`],
+    /// which the next children get crossed over into
+    spare: Vec<Chromosome>,
 }
 
 impl<S> GeneticAlgorithm<S>
@@ -21,34 +30,199 @@ where
             selection_method,
             crossover_method: Box::new(crossover_method),
             mutation_method: Box::new(mutation_method),
+            pool: Vec::new(),
+            spare: Vec::new(),
         }
     }
 
+    pub fn selection_method(&self) -> &S {
+        &self.selection_method
+    }
+
+    /// Returns the selection method, e.g. for
+    /// [`BoltzmannSelection::advance()`] after each generation.
+    pub fn selection_method_mut(&mut self) -> &mut S {
+        &mut self.selection_method
+    }
+
     pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
     where
         I: Individual,
+    {
+        let mut offspring = Vec::with_capacity(population.len());
+
+        self.breed_into(
+            rng,
+            population,
+            &mut offspring,
+            &mut Vec::new(),
+            &mut Vec::new(),
+        );
+
+        offspring
+    }
+
+    /// Like [`Self::evolve()`], but writes the new generation into
+    /// `offspring` (clearing it first), so that its allocation can be reused
+    /// across generations:
+    ///
+    /// ```ignore
+    /// ga.evolve_into(&mut rng, &population, &mut offspring);
+    /// std::mem::swap(&mut population, &mut offspring);
+    /// ```
+    ///
+    /// The individuals found in `offspring` get turned back into chromosomes
+    /// (see [`Individual::into_chromosome()`]), which the children are then
+    /// crossed over into (see [`CrossoverMethod::crossover_into()`]); the
+    /// mating pool of selection methods that draw it all at once (see
+    /// [`SelectionMethod::pools()`]) gets reused as well.
+    pub fn evolve_into<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        offspring: &mut Vec<I>,
+    ) where
+        I: Individual,
+    {
+        let mut pool = std::mem::take(&mut self.pool);
+        let mut spare = std::mem::take(&mut self.spare);
+
+        spare.extend(offspring.drain(..).map(I::into_chromosome));
+
+        self.breed_into(rng, population, offspring, &mut pool, &mut spare);
+        self.pool = pool;
+        self.spare = spare;
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn breed_into<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        offspring: &mut Vec<I>,
+        pool: &mut Vec<usize>,
+        spare: &mut Vec<Chromosome>,
+    ) where
+        I: Individual,
     {
         assert!(!population.is_empty());
 
-        (0..population.len())
-            .map(|_| {
-                let parent_a = self.selection_method.select(rng, population).chromosome();
-                let parent_b = self.selection_method.select(rng, population).chromosome();
+        offspring.clear();
 
-                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+        if self.selection_method.pools() {
+            pool.clear();
 
-                self.mutation_method.mutate(rng, &mut child);
+            self.selection_method
+                .select_many(rng, population, 2 * population.len(), pool);
 
-                I::create(child)
-            })
-            .collect()
+            offspring.extend(pool.chunks(2).map(|parents| {
+                let child = spare.pop().unwrap_or_default();
+
+                self.breed(rng, &population[parents[0]], &population[parents[1]], child)
+            }));
+        } else {
+            offspring.extend((0..population.len()).map(|_| {
+                let parent_a = self.selection_method.select(rng, population);
+                let parent_b = self.selection_method.select(rng, population);
+                let child = spare.pop().unwrap_or_default();
+
+                self.breed(rng, parent_a, parent_b, child)
+            }));
+        }
+    }
+
+    fn breed<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &I,
+        parent_b: &I,
+        mut child: Chromosome,
+    ) -> I
+    where
+        I: Individual,
+    {
+        let parent_a = parent_a.chromosome();
+        let parent_b = parent_b.chromosome();
+
+        self.crossover_method
+            .crossover_into(rng, parent_a, parent_b, &mut child);
+
+        self.mutation_method.mutate(rng, &mut child);
+
+        I::create(child)
+    }
+
+    /// Like [`Self::evolve()`], but returns an error instead of panicking
+    /// when the population is empty or its chromosomes differ in length.
+    pub fn try_evolve<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+    ) -> Result<Vec<I>, GeneticAlgorithmError>
+    where
+        I: Individual,
+    {
+        let expected = population
+            .first()
+            .ok_or(GeneticAlgorithmError::EmptyPopulation)?
+            .chromosome()
+            .len();
+
+        if let Some(individual) = population
+            .iter()
+            .find(|individual| individual.chromosome().len() != expected)
+        {
+            return Err(GeneticAlgorithmError::ChromosomeLengthMismatch {
+                expected,
+                actual: individual.chromosome().len(),
+            });
+        }
+
+        Ok(self.evolve(rng, population))
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Error)]
+pub enum GeneticAlgorithmError {
+    #[error("the population is empty")]
+    EmptyPopulation,
+
+    #[error("expected chromosomes of length {expected}, got {actual}")]
+    ChromosomeLengthMismatch { expected: usize, actual: usize },
+
+    #[error("mutation chance must be within 0.0..=1.0, got {chance}")]
+    InvalidMutationChance { chance: f32 },
+}
+
 pub trait Individual {
     fn create(chromosome: Chromosome) -> Self;
     fn chromosome(&self) -> &Chromosome;
     fn fitness(&self) -> f32;
+
+    /// Turns the individual back into its chromosome, so that
+    /// [`GeneticAlgorithm::evolve_into()`] can reuse its genes' allocation;
+    /// by default, the chromosome gets cloned.
+    fn into_chromosome(self) -> Chromosome
+    where
+        Self: Sized,
+    {
+        self.chromosome().clone()
+    }
+
+    /// Returns the number of cases (e.g. scenarios) the individual's been
+    /// evaluated on, see [`LexicaseSelection`]; all individuals of a
+    /// population must have the same number of cases.
+    fn cases(&self) -> usize {
+        1
+    }
+
+    /// Returns the fitness on given case; by default, there's just a single
+    /// case and its fitness is [`Self::fitness()`].
+    fn case_fitness(&self, case: usize) -> f32 {
+        let _ = case;
+
+        self.fitness()
+    }
 }
 
 pub struct RouletteWheelSelection;
@@ -76,10 +250,377 @@ impl SelectionMethod for RouletteWheelSelection {
     }
 }
 
+/// Sorts the population by fitness and selects individuals proportionally
+/// to their rank - the least fit one has weight `1`, the fittest one has
+/// weight `population.len()`; unlike [`RouletteWheelSelection`], one
+/// individual that's ten times fitter than everyone else gets selected no
+/// more often than one that's just slightly fitter.
+pub struct RankSelection;
+
+impl RankSelection {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RankSelection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelectionMethod for RankSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        let mut ranked: Vec<_> = population.iter().collect();
+
+        ranked.sort_by(|a, b| a.fitness().total_cmp(&b.fitness()));
+
+        let rank = (1..=ranked.len())
+            .collect::<Vec<_>>()
+            .choose_weighted(rng, |&rank| rank)
+            .copied()
+            .expect("the population is empty");
+
+        ranked[rank - 1]
+    }
+
+    fn pools(&self) -> bool {
+        true
+    }
+
+    /// Sorts the population just once, instead of with each selection.
+    fn select_many<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        count: usize,
+        pool: &mut Vec<usize>,
+    ) where
+        I: Individual,
+    {
+        assert!(!population.is_empty(), "the population is empty");
+
+        // The ranking goes at the end of the pool (so that the pool's
+        // allocation gets reused for it), followed by the selected
+        // individuals; afterwards the ranking is removed
+        let ranked = pool.len();
+        let len = population.len();
+
+        pool.extend(0..len);
+
+        pool[ranked..].sort_unstable_by(|&a, &b| {
+            population[a]
+                .fitness()
+                .total_cmp(&population[b].fitness())
+                .then(a.cmp(&b))
+        });
+
+        // Rank `r` (counting from 1) has weight `r`, i.e. it covers
+        // `[r * (r - 1) / 2, r * (r + 1) / 2)` of the total weight
+        let total = len * (len + 1) / 2;
+
+        for _ in 0..count {
+            let weight = rng.gen_range(0..total);
+            let rank = (((8 * weight + 1) as f64).sqrt() as usize).div_ceil(2);
+
+            pool.push(pool[ranked + rank - 1]);
+        }
+
+        pool.drain(ranked..ranked + len);
+    }
+}
+
+/// Picks `size` individuals at random (with repetition) and selects the
+/// fittest of them; unlike [`RouletteWheelSelection`], it only cares about
+/// the fitness ranking, so it works with negative fitness and doesn't let a
+/// single outlier take over the population. Larger tournaments favor the
+/// fittest individuals more strongly.
+pub struct TournamentSelection {
+    size: usize,
+}
+
+impl TournamentSelection {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "tournament size must be positive");
+
+        Self { size }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl SelectionMethod for TournamentSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        (0..self.size)
+            .map(|_| population.choose(rng).expect("the population is empty"))
+            .reduce(|best, individual| {
+                if individual.fitness() > best.fitness() {
+                    individual
+                } else {
+                    best
+                }
+            })
+            .unwrap()
+    }
+}
+
+/// Selects individuals proportionally to `exp(fitness / temperature)` - high
+/// temperatures make the selection nearly uniform, low ones make it nearly
+/// always pick the fittest individual.
+///
+/// The temperature follows a schedule of generations, so that selection
+/// pressure can be annealed over a long run:
+///
+/// ```ignore
+/// let mut ga = GeneticAlgorithm::new(
+///     BoltzmannSelection::with_schedule(|generation| 10.0 * 0.99f32.powi(generation as _)),
+///     UniformCrossover::new(),
+///     GaussianMutation::new(0.01, 0.3),
+/// );
+///
+/// loop {
+///     population = ga.evolve(&mut rng, &population);
+///     ga.selection_method_mut().advance();
+/// }
+/// ```
+pub struct BoltzmannSelection {
+    schedule: Box<dyn Fn(usize) -> f32>,
+    generation: usize,
+}
+
+impl BoltzmannSelection {
+    /// Creates a selection method with constant temperature.
+    pub fn new(temperature: f32) -> Self {
+        Self::with_schedule(move |_| temperature)
+    }
+
+    /// Creates a selection method whose temperature is `schedule(generation)`,
+    /// starting at generation 0.
+    pub fn with_schedule(schedule: impl Fn(usize) -> f32 + 'static) -> Self {
+        Self {
+            schedule: Box::new(schedule),
+            generation: 0,
+        }
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn temperature(&self) -> f32 {
+        (self.schedule)(self.generation)
+    }
+
+    /// Moves on to the next generation of the schedule.
+    pub fn advance(&mut self) {
+        self.generation += 1;
+    }
+}
+
+impl SelectionMethod for BoltzmannSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        let temperature = self.temperature();
+
+        assert!(
+            temperature.is_finite() && temperature > 0.0,
+            "temperature must be finite and positive, got {temperature}"
+        );
+
+        // Shifting by the largest fitness keeps `exp()` from overflowing,
+        // without changing the proportions
+        let max = population
+            .iter()
+            .map(|individual| individual.fitness())
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        population
+            .choose_weighted(rng, |individual| {
+                ((individual.fitness() - max) / temperature).exp()
+            })
+            .expect("the population is empty")
+    }
+}
+
+/// Goes through the cases of [`Individual::case_fitness()`] in random order,
+/// each time keeping just the individuals that are the best on that case,
+/// until one individual is left (or the cases run out, in which case one of
+/// the remaining individuals is picked at random).
+///
+/// Unlike methods that look at the aggregate [`Individual::fitness()`], this
+/// keeps specialists around - an individual that's the best on any single
+/// case gets selected sometimes, even if it's bad on all of the others.
+pub struct LexicaseSelection;
+
+impl LexicaseSelection {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for LexicaseSelection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelectionMethod for LexicaseSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        let mut candidates: Vec<_> = population.iter().collect();
+        let mut cases: Vec<_> =
+            (0..candidates.first().expect("the population is empty").cases()).collect();
+
+        cases.shuffle(rng);
+
+        for case in cases {
+            if candidates.len() == 1 {
+                break;
+            }
+
+            let best = candidates
+                .iter()
+                .map(|individual| individual.case_fitness(case))
+                .fold(f32::NEG_INFINITY, f32::max);
+
+            // NaN is never the best, unless it's all that's left - in which
+            // case this case can't tell the candidates apart
+            if candidates
+                .iter()
+                .any(|individual| individual.case_fitness(case) >= best)
+            {
+                candidates.retain(|individual| individual.case_fitness(case) >= best);
+            }
+        }
+
+        candidates.choose(rng).unwrap()
+    }
+}
+
+/// Like [`RouletteWheelSelection`], but draws the whole mating pool at once,
+/// with evenly spaced pointers on the wheel - each individual gets selected
+/// either `floor` or `ceil` of its expected number of times, so (especially
+/// in small populations) luck can't make the fittest individuals miss out.
+pub struct StochasticUniversalSampling;
+
+impl StochasticUniversalSampling {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for StochasticUniversalSampling {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelectionMethod for StochasticUniversalSampling {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        // With a single pointer, this is just the roulette wheel
+        RouletteWheelSelection.select(rng, population)
+    }
+
+    fn pools(&self) -> bool {
+        true
+    }
+
+    fn select_many<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        count: usize,
+        pool: &mut Vec<usize>,
+    ) where
+        I: Individual,
+    {
+        if count == 0 {
+            return;
+        }
+
+        assert!(!population.is_empty(), "the population is empty");
+
+        let total: f32 = population
+            .iter()
+            .map(|individual| individual.fitness())
+            .sum();
+
+        assert!(total > 0.0, "the total fitness must be positive");
+
+        let step = total / count as f32;
+        let start = rng.gen_range(0.0..step);
+
+        let mut current = 0;
+        let mut cumulative = population[0].fitness();
+        let drawn = pool.len();
+
+        pool.extend((0..count).map(|id| {
+            let pointer = start + id as f32 * step;
+
+            // Rounding may leave the last pointer past the end of the
+            // wheel, in which case it stays on the last individual
+            while cumulative <= pointer && current + 1 < population.len() {
+                current += 1;
+                cumulative += population[current].fitness();
+            }
+
+            current
+        }));
+
+        // Pointers select individuals in the population's order, which would
+        // otherwise make neighbors mate with each other
+        pool[drawn..].shuffle(rng);
+    }
+}
+
 pub trait SelectionMethod {
     fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
     where
         I: Individual;
+
+    /// Whether [`GeneticAlgorithm`] should draw all parents of a generation
+    /// at once with [`Self::select_many()`], instead of calling
+    /// [`Self::select()`] for each of them.
+    fn pools(&self) -> bool {
+        false
+    }
+
+    /// Selects `count` individuals at once, e.g. the whole mating pool of a
+    /// generation, appending their indices in `population` to `pool`; calls
+    /// [`Self::select()`] `count` times by default.
+    fn select_many<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        count: usize,
+        pool: &mut Vec<usize>,
+    ) where
+        I: Individual,
+    {
+        pool.extend((0..count).map(|_| {
+            let selected = self.select(rng, population);
+
+            population
+                .iter()
+                .position(|individual| std::ptr::eq(individual, selected))
+                .unwrap()
+        }));
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +631,9 @@ pub enum TestIndividual {
 
     /// For tests that don't require access to chromosome
     WithFitness { fitness: f32 },
+
+    /// For tests that require fitness on each of several cases
+    WithCases { cases: Vec<f32> },
 }
 
 #[cfg(test)]
@@ -116,8 +660,18 @@ impl Individual for TestIndividual {
         match self {
             Self::WithChromosome { chromosome } => chromosome,
 
-            Self::WithFitness { .. } => {
-                panic!("not supported for TestIndividual::WithFitness")
+            Self::WithFitness { .. } | Self::WithCases { .. } => {
+                panic!("not supported for TestIndividual::WithFitness nor WithCases")
+            }
+        }
+    }
+
+    fn into_chromosome(self) -> Chromosome {
+        match self {
+            Self::WithChromosome { chromosome } => chromosome,
+
+            Self::WithFitness { .. } | Self::WithCases { .. } => {
+                panic!("not supported for TestIndividual::WithFitness nor WithCases")
             }
         }
     }
@@ -127,6 +681,22 @@ impl Individual for TestIndividual {
             Self::WithChromosome { chromosome } => chromosome.iter().sum(),
 
             Self::WithFitness { fitness } => *fitness,
+
+            Self::WithCases { cases } => cases.iter().sum(),
+        }
+    }
+
+    fn cases(&self) -> usize {
+        match self {
+            Self::WithCases { cases } => cases.len(),
+            _ => 1,
+        }
+    }
+
+    fn case_fitness(&self, case: usize) -> f32 {
+        match self {
+            Self::WithCases { cases } => cases[case],
+            _ => self.fitness(),
         }
     }
 }
@@ -155,21 +725,407 @@ mod tests {
             .fold(Default::default(), |mut histogram, individual| {
                 *histogram.entry(individual.fitness() as _).or_default() += 1;
 
-                histogram
-            });
+                histogram
+            });
+
+        let expected_histogram = maplit::btreemap! {
+            1 => 98,
+            2 => 202,
+            3 => 278,
+            4 => 422,
+        };
+
+        assert_eq!(actual_histogram, expected_histogram);
+    }
+
+    mod rank {
+        use super::*;
+
+        /// Weights are 1, 2, 3 and 4 no matter how much fitter the fittest
+        /// individual is
+        #[test]
+        fn test() {
+            let method = RankSelection::new();
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let population = vec![
+                TestIndividual::new(2.0),
+                TestIndividual::new(1.0),
+                TestIndividual::new(400.0),
+                TestIndividual::new(3.0),
+            ];
+
+            let actual_histogram: BTreeMap<i32, _> = (0..1000)
+                .map(|_| method.select(&mut rng, &population))
+                .fold(Default::default(), |mut histogram, individual| {
+                    *histogram.entry(individual.fitness() as _).or_default() += 1;
+
+                    histogram
+                });
+
+            let expected_histogram = maplit::btreemap! {
+                1 => 95,
+                2 => 194,
+                3 => 310,
+                400 => 401,
+            };
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn select_many() {
+            let method = RankSelection::new();
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let population = vec![
+                TestIndividual::new(2.0),
+                TestIndividual::new(1.0),
+                TestIndividual::new(400.0),
+                TestIndividual::new(3.0),
+            ];
+
+            let mut pool = vec![0];
+
+            method.select_many(&mut rng, &population, 1000, &mut pool);
+
+            assert_eq!(pool.len(), 1001);
+            assert_eq!(pool[0], 0);
+
+            let actual_histogram: BTreeMap<i32, _> =
+                pool[1..]
+                    .iter()
+                    .fold(Default::default(), |mut histogram, &id| {
+                        *histogram.entry(population[id].fitness() as _).or_default() += 1;
+
+                        histogram
+                    });
+
+            let expected_histogram = maplit::btreemap! {
+                1 => 103,
+                2 => 201,
+                3 => 304,
+                400 => 392,
+            };
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+    }
+
+    mod boltzmann {
+        use super::*;
+
+        fn histogram(method: &BoltzmannSelection) -> BTreeMap<i32, usize> {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let population = vec![
+                TestIndividual::new(-2.0),
+                TestIndividual::new(1.0),
+                TestIndividual::new(4.0),
+                TestIndividual::new(3.0),
+            ];
+
+            (0..1000)
+                .map(|_| method.select(&mut rng, &population))
+                .fold(Default::default(), |mut histogram, individual| {
+                    *histogram.entry(individual.fitness() as _).or_default() += 1;
+
+                    histogram
+                })
+        }
+
+        #[test]
+        fn test() {
+            let expected_histogram = maplit::btreemap! {
+                -2 => 35,
+                1 => 111,
+                3 => 307,
+                4 => 547,
+            };
+
+            assert_eq!(histogram(&BoltzmannSelection::new(2.0)), expected_histogram);
+        }
+
+        #[test]
+        fn cold() {
+            let expected_histogram = maplit::btreemap! {
+                4 => 1000,
+            };
+
+            assert_eq!(
+                histogram(&BoltzmannSelection::new(0.01)),
+                expected_histogram
+            );
+        }
+
+        #[test]
+        fn schedule() {
+            let mut method =
+                BoltzmannSelection::with_schedule(|generation| 100.0 / (generation + 1) as f32);
+
+            assert_eq!(method.temperature(), 100.0);
+
+            method.advance();
+            method.advance();
+
+            assert_eq!(method.generation(), 2);
+            approx::assert_relative_eq!(method.temperature(), 100.0 / 3.0);
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_zero_temperature() {
+            histogram(&BoltzmannSelection::new(0.0));
+        }
+    }
+
+    mod lexicase {
+        use super::*;
+
+        #[test]
+        fn keeps_specialists() {
+            let method = LexicaseSelection::new();
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            // The generalist has the best total fitness, but it isn't the
+            // best on any case
+            let population = vec![
+                TestIndividual::WithCases {
+                    cases: vec![10.0, 0.0, 0.0],
+                },
+                TestIndividual::WithCases {
+                    cases: vec![0.0, 10.0, 0.0],
+                },
+                TestIndividual::WithCases {
+                    cases: vec![0.0, 0.0, 10.0],
+                },
+                TestIndividual::WithCases {
+                    cases: vec![9.0, 9.0, 9.0],
+                },
+            ];
+
+            let actual_histogram: BTreeMap<usize, usize> = (0..1000)
+                .map(|_| method.select(&mut rng, &population))
+                .fold(Default::default(), |mut histogram, individual| {
+                    let id = population.iter().position(|other| other == individual);
+
+                    *histogram.entry(id.unwrap()).or_default() += 1;
+                    histogram
+                });
+
+            let expected_histogram = maplit::btreemap! {
+                0 => 348,
+                1 => 317,
+                2 => 335,
+            };
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn breaks_ties_on_later_cases() {
+            let method = LexicaseSelection::new();
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let population = vec![
+                TestIndividual::WithCases {
+                    cases: vec![5.0, 1.0],
+                },
+                TestIndividual::WithCases {
+                    cases: vec![5.0, 2.0],
+                },
+                TestIndividual::WithCases {
+                    cases: vec![4.0, 3.0],
+                },
+            ];
+
+            // The first individual ties with the second one on the first
+            // case and it's worse than both of them on the second case
+            for _ in 0..100 {
+                let selected = method.select(&mut rng, &population);
+
+                assert_ne!(selected, &population[0]);
+            }
+        }
+
+        #[test]
+        fn single_case() {
+            let method = LexicaseSelection::new();
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let population = vec![
+                TestIndividual::new(2.0),
+                TestIndividual::new(4.0),
+                TestIndividual::new(1.0),
+            ];
+
+            for _ in 0..100 {
+                assert_eq!(method.select(&mut rng, &population).fitness(), 4.0);
+            }
+        }
+
+        #[test]
+        fn nan() {
+            let method = LexicaseSelection::new();
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let population = vec![
+                TestIndividual::WithCases {
+                    cases: vec![f32::NAN, 1.0],
+                },
+                TestIndividual::WithCases {
+                    cases: vec![f32::NAN, 2.0],
+                },
+                TestIndividual::WithCases {
+                    cases: vec![f32::NAN, f32::NAN],
+                },
+            ];
+
+            // The first case doesn't rule anybody out, and NaN loses the
+            // second one just like 1.0 does
+            for _ in 0..100 {
+                let selected = method.select(&mut rng, &population);
+
+                assert!(std::ptr::eq(selected, &population[1]));
+            }
+
+            let population = vec![TestIndividual::new(f32::NAN), TestIndividual::new(f32::NAN)];
+
+            for _ in 0..100 {
+                let selected = method.select(&mut rng, &population);
+
+                assert!(population.iter().any(|other| std::ptr::eq(selected, other)));
+            }
+        }
+    }
+
+    mod stochastic_universal_sampling {
+        use super::*;
+
+        fn population() -> Vec<TestIndividual> {
+            vec![
+                TestIndividual::new(2.0),
+                TestIndividual::new(1.0),
+                TestIndividual::new(4.0),
+                TestIndividual::new(3.0),
+            ]
+        }
+
+        /// Fitness adds up to 10.0, so ten pointers select each individual
+        /// exactly as many times as its fitness, no matter the seed
+        #[test]
+        fn test() {
+            let method = StochasticUniversalSampling::new();
+            let population = population();
+
+            for seed in 0..10 {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+                let mut pool = Vec::new();
+
+                method.select_many(&mut rng, &population, 10, &mut pool);
+
+                let actual_histogram: BTreeMap<i32, _> =
+                    pool.into_iter()
+                        .fold(Default::default(), |mut histogram, id| {
+                            *histogram.entry(population[id].fitness() as _).or_default() += 1;
+
+                            histogram
+                        });
+
+                let expected_histogram = maplit::btreemap! {
+                    1 => 1,
+                    2 => 2,
+                    3 => 3,
+                    4 => 4,
+                };
+
+                assert_eq!(actual_histogram, expected_histogram);
+            }
+        }
+
+        #[test]
+        fn skips_zero_fitness() {
+            let method = StochasticUniversalSampling::new();
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = vec![TestIndividual::new(0.0), TestIndividual::new(1.0)];
+
+            let mut pool = vec![0];
+
+            method.select_many(&mut rng, &population, 3, &mut pool);
+
+            assert_eq!(pool, [0, 1, 1, 1]);
+        }
+
+        #[test]
+        fn select() {
+            let method = StochasticUniversalSampling::new();
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = population();
+
+            assert!(population.contains(method.select(&mut rng, &population)));
+        }
+    }
+
+    mod tournament {
+        use super::*;
+
+        fn histogram(size: usize) -> BTreeMap<i32, usize> {
+            let method = TournamentSelection::new(size);
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let population = vec![
+                TestIndividual::new(-2.0),
+                TestIndividual::new(1.0),
+                TestIndividual::new(4.0),
+                TestIndividual::new(3.0),
+            ];
+
+            (0..1000)
+                .map(|_| method.select(&mut rng, &population))
+                .fold(Default::default(), |mut histogram, individual| {
+                    *histogram.entry(individual.fitness() as _).or_default() += 1;
+
+                    histogram
+                })
+        }
+
+        /// Individuals ranked 1st to 4th are expected to win 7/16, 5/16, 3/16
+        /// and 1/16 of the tournaments of two
+        #[test]
+        fn test() {
+            let expected_histogram = maplit::btreemap! {
+                -2 => 56,
+                1 => 189,
+                3 => 299,
+                4 => 456,
+            };
+
+            assert_eq!(histogram(2), expected_histogram);
+        }
 
-        let expected_histogram = maplit::btreemap! {
-            1 => 98,
-            2 => 202,
-            3 => 278,
-            4 => 422,
-        };
+        /// Tournaments of one are uniformly random
+        #[test]
+        fn of_one() {
+            let expected_histogram = maplit::btreemap! {
+                -2 => 246,
+                1 => 262,
+                3 => 238,
+                4 => 254,
+            };
 
-        assert_eq!(actual_histogram, expected_histogram);
+            assert_eq!(histogram(1), expected_histogram);
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_zero_size() {
+            TournamentSelection::new(0);
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Chromosome {
     genes: Vec<f32>,
 }
@@ -325,6 +1281,35 @@ pub trait CrossoverMethod {
         parent_a: &Chromosome,
         parent_b: &Chromosome,
     ) -> Chromosome;
+
+    /// Like [`Self::crossover()`], but writes the child into `child`
+    /// (replacing its genes), so that its allocation can be reused; calls
+    /// [`Self::crossover()`] by default.
+    fn crossover_into(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+        child: &mut Chromosome,
+    ) {
+        *child = self.crossover(rng, parent_a, parent_b);
+    }
+}
+
+/// Runs [`CrossoverMethod::crossover_into()`] on a new chromosome, for the
+/// built-in methods, which cross over in place.
+fn crossover_new(
+    method: &impl CrossoverMethod,
+    rng: &mut dyn RngCore,
+    parent_a: &Chromosome,
+    parent_b: &Chromosome,
+) -> Chromosome {
+    let mut child = Chromosome {
+        genes: Vec::with_capacity(parent_a.len()),
+    };
+
+    method.crossover_into(rng, parent_a, parent_b, &mut child);
+    child
 }
 
 #[derive(Clone, Debug)]
@@ -349,15 +1334,210 @@ impl CrossoverMethod for UniformCrossover {
         parent_a: &Chromosome,
         parent_b: &Chromosome,
     ) -> Chromosome {
+        crossover_new(self, rng, parent_a, parent_b)
+    }
+
+    fn crossover_into(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+        child: &mut Chromosome,
+    ) {
         assert_eq!(parent_a.len(), parent_b.len());
 
+        child.genes.clear();
+
         let parent_a = parent_a.iter();
         let parent_b = parent_b.iter();
 
-        parent_a
+        let genes = parent_a
             .zip(parent_b)
-            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
-            .collect()
+            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b });
+
+        child.genes.extend(genes);
+    }
+}
+
+/// Cuts the parents at `points` random places and builds the child from
+/// alternating segments of theirs, starting with `parent_a` - unlike
+/// [`UniformCrossover`], it keeps runs of neighboring genes (e.g. all
+/// weights of a neuron) together.
+///
+/// `1` point gives the classic single-point crossover; chromosomes shorter
+/// than `points + 1` genes get cut after each gene.
+#[derive(Clone, Debug)]
+pub struct MultiPointCrossover {
+    points: usize,
+}
+
+impl MultiPointCrossover {
+    pub fn new(points: usize) -> Self {
+        assert!(points > 0, "there must be at least one crossover point");
+
+        Self { points }
+    }
+
+    pub fn points(&self) -> usize {
+        self.points
+    }
+}
+
+impl CrossoverMethod for MultiPointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        crossover_new(self, rng, parent_a, parent_b)
+    }
+
+    fn crossover_into(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+        child: &mut Chromosome,
+    ) {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        child.genes.clear();
+
+        // Cutting before the first gene wouldn't do anything
+        let places = parent_a.len().saturating_sub(1);
+        let mut cuts = rand::seq::index::sample(rng, places, self.points.min(places)).into_vec();
+
+        cuts.sort_unstable();
+
+        let mut cuts = cuts.into_iter().map(|cut| cut + 1).peekable();
+        let mut from_a = true;
+
+        let genes = parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .enumerate()
+            .map(|(id, (&a, &b))| {
+                if cuts.next_if_eq(&id).is_some() {
+                    from_a = !from_a;
+                }
+
+                if from_a {
+                    a
+                } else {
+                    b
+                }
+            });
+
+        child.genes.extend(genes);
+    }
+}
+
+/// Interpolates between the parents - the child is
+/// `weight * parent_a + (1 - weight) * parent_b`, with `weight` drawn from
+/// `0.0..=1.0` for each child, so it lies on the line between them.
+#[derive(Clone, Debug)]
+pub struct ArithmeticCrossover;
+
+impl ArithmeticCrossover {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ArithmeticCrossover {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrossoverMethod for ArithmeticCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        crossover_new(self, rng, parent_a, parent_b)
+    }
+
+    fn crossover_into(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+        child: &mut Chromosome,
+    ) {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        child.genes.clear();
+
+        let weight = rng.gen_range(0.0..=1.0);
+
+        let genes = parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| weight * a + (1.0 - weight) * b);
+
+        child.genes.extend(genes);
+    }
+}
+
+/// Blend crossover (BLX-α) - draws each gene from the range between the
+/// parents' genes, extended by `alpha` times its width on both sides, so
+/// that children can also explore a bit beyond their parents:
+/// - 0.0 = genes will stay between the parents' ones
+/// - 0.5 = genes may go past them by half of their distance (a common
+///   choice, which keeps the population's spread from shrinking)
+#[derive(Clone, Debug)]
+pub struct BlendCrossover {
+    alpha: f32,
+}
+
+impl BlendCrossover {
+    pub fn new(alpha: f32) -> Self {
+        assert!(
+            alpha.is_finite() && alpha >= 0.0,
+            "alpha must be finite and non-negative"
+        );
+
+        Self { alpha }
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+}
+
+impl CrossoverMethod for BlendCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        crossover_new(self, rng, parent_a, parent_b)
+    }
+
+    fn crossover_into(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+        child: &mut Chromosome,
+    ) {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        child.genes.clear();
+
+        let genes = parent_a.iter().zip(parent_b.iter()).map(|(&a, &b)| {
+            let (min, max) = if a < b { (a, b) } else { (b, a) };
+            let margin = self.alpha * (max - min);
+
+            rng.gen_range((min - margin)..=(max + margin))
+        });
+
+        child.genes.extend(genes);
     }
 }
 
@@ -380,6 +1560,129 @@ mod crossover_tests {
         assert_eq!(diff_a, 49);
         assert_eq!(diff_b, 51);
     }
+
+    mod multi_point {
+        use super::*;
+
+        fn parents(len: usize) -> (Chromosome, Chromosome) {
+            (
+                (1..=len).map(|n| n as f32).collect(),
+                (1..=len).map(|n| -(n as f32)).collect(),
+            )
+        }
+
+        /// Returns whether each gene of the child comes from `parent_a`
+        fn origins(points: usize, len: usize) -> Vec<bool> {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let (parent_a, parent_b) = parents(len);
+
+            MultiPointCrossover::new(points)
+                .crossover(&mut rng, &parent_a, &parent_b)
+                .iter()
+                .map(|gene| *gene > 0.0)
+                .collect()
+        }
+
+        fn segments(origins: &[bool]) -> usize {
+            1 + origins.windows(2).filter(|pair| pair[0] != pair[1]).count()
+        }
+
+        #[test]
+        fn single_point() {
+            let origins = origins(1, 100);
+            let cut = origins.iter().position(|from_a| !from_a).unwrap();
+
+            assert!(cut > 0);
+            assert!(origins[..cut].iter().all(|&from_a| from_a));
+            assert!(origins[cut..].iter().all(|&from_a| !from_a));
+        }
+
+        #[test]
+        fn test() {
+            let origins = origins(3, 100);
+
+            assert!(origins[0]);
+            assert_eq!(segments(&origins), 4);
+        }
+
+        #[test]
+        fn more_points_than_genes() {
+            assert_eq!(origins(10, 4), [true, false, true, false]);
+            assert_eq!(origins(10, 1), [true]);
+            assert!(origins(10, 0).is_empty());
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_zero_points() {
+            MultiPointCrossover::new(0);
+        }
+    }
+
+    mod arithmetic {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let parent_a: Chromosome = [1.0, 2.0, -4.0].into_iter().collect();
+            let parent_b: Chromosome = [3.0, 2.0, 4.0].into_iter().collect();
+
+            let child = ArithmeticCrossover::new().crossover(&mut rng, &parent_a, &parent_b);
+
+            // All genes share the same weight
+            let weight = (child[0] - 3.0) / (1.0 - 3.0);
+
+            assert!((0.0..=1.0).contains(&weight));
+            assert_eq!(child[1], 2.0);
+            approx::assert_relative_eq!(child[2], weight * -4.0 + (1.0 - weight) * 4.0);
+        }
+    }
+
+    mod blend {
+        use super::*;
+
+        fn children(alpha: f32) -> Vec<f32> {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let parent_a: Chromosome = [1.0; 1000].into_iter().collect();
+            let parent_b: Chromosome = [3.0; 1000].into_iter().collect();
+
+            BlendCrossover::new(alpha)
+                .crossover(&mut rng, &parent_a, &parent_b)
+                .into_iter()
+                .collect()
+        }
+
+        #[test]
+        fn test() {
+            let children = children(0.5);
+
+            assert!(children.iter().all(|gene| (0.0..=4.0).contains(gene)));
+            assert!(children.iter().any(|&gene| gene < 1.0));
+            assert!(children.iter().any(|&gene| gene > 3.0));
+        }
+
+        #[test]
+        fn zero_alpha() {
+            assert!(children(0.0).iter().all(|gene| (1.0..=3.0).contains(gene)));
+        }
+
+        #[test]
+        fn same_genes() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let parent: Chromosome = [1.0, -2.0].into_iter().collect();
+
+            let child = BlendCrossover::new(0.5).crossover(&mut rng, &parent, &parent);
+
+            assert_eq!(child, parent);
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_negative_alpha() {
+            BlendCrossover::new(-0.5);
+        }
+    }
 }
 
 pub trait MutationMethod {
@@ -405,6 +1708,16 @@ impl GaussianMutation {
 
         Self { chance, coeff }
     }
+
+    /// Like [`Self::new()`], but returns an error instead of panicking when
+    /// `chance` is outside of `0.0..=1.0`.
+    pub fn try_new(chance: f32, coeff: f32) -> Result<Self, GeneticAlgorithmError> {
+        if !(0.0..=1.0).contains(&chance) {
+            return Err(GeneticAlgorithmError::InvalidMutationChance { chance });
+        }
+
+        Ok(Self::new(chance, coeff))
+    }
 }
 
 impl MutationMethod for GaussianMutation {
@@ -435,6 +1748,23 @@ mod mutation_tests {
         child.into_iter().collect()
     }
 
+    mod try_new {
+        use super::*;
+
+        #[test]
+        fn rejects_chance_out_of_range() {
+            assert_eq!(
+                GaussianMutation::try_new(1.5, 0.5).err(),
+                Some(GeneticAlgorithmError::InvalidMutationChance { chance: 1.5 })
+            );
+        }
+
+        #[test]
+        fn accepts_chance_in_range() {
+            assert!(GaussianMutation::try_new(0.5, 0.5).is_ok());
+        }
+    }
+
     mod given_zero_chance {
         fn actual(coeff: f32) -> Vec<f32> {
             super::actual(0.0, coeff)
@@ -577,4 +1907,180 @@ mod algorithm_tests {
 
         assert_eq!(population, expected_population);
     }
+
+    mod evolve_into {
+        use super::*;
+
+        fn population() -> Vec<TestIndividual> {
+            vec![
+                individual(&[0.0, 0.0, 0.0]),
+                individual(&[1.0, 1.0, 1.0]),
+                individual(&[1.0, 2.0, 1.0]),
+                individual(&[1.0, 2.0, 4.0]),
+            ]
+        }
+
+        #[test]
+        fn matches_evolve() {
+            let mut ga = GeneticAlgorithm::new(
+                RouletteWheelSelection::new(),
+                UniformCrossover::new(),
+                GaussianMutation::new(0.5, 0.5),
+            );
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let expected = ga.evolve(&mut rng, &population());
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut actual = vec![individual(&[9.0, 9.0, 9.0])];
+            ga.evolve_into(&mut rng, &population(), &mut actual);
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn matches_evolve_when_pooled() {
+            let mut ga = GeneticAlgorithm::new(
+                StochasticUniversalSampling::new(),
+                UniformCrossover::new(),
+                GaussianMutation::new(0.5, 0.5),
+            );
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let expected = ga.evolve(&mut rng, &population());
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut actual = Vec::new();
+            ga.evolve_into(&mut rng, &population(), &mut actual);
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn reuses_offspring_buffer() {
+            let mut ga = GeneticAlgorithm::new(
+                RouletteWheelSelection::new(),
+                UniformCrossover::new(),
+                GaussianMutation::new(0.5, 0.5),
+            );
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut population = population();
+            let mut offspring = Vec::with_capacity(population.len());
+            let buffers = [population.as_ptr(), offspring.as_ptr()];
+
+            for _ in 0..10 {
+                ga.evolve_into(&mut rng, &population, &mut offspring);
+                std::mem::swap(&mut population, &mut offspring);
+            }
+
+            assert_eq!(population.len(), 4);
+            assert_eq!(buffers, [population.as_ptr(), offspring.as_ptr()]);
+        }
+
+        #[test]
+        fn reuses_pool() {
+            let mut ga = GeneticAlgorithm::new(
+                StochasticUniversalSampling::new(),
+                UniformCrossover::new(),
+                GaussianMutation::new(0.5, 0.5),
+            );
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut population = population();
+            let mut offspring = Vec::new();
+
+            ga.evolve_into(&mut rng, &population, &mut offspring);
+
+            let pool = ga.pool.as_ptr();
+
+            for _ in 0..10 {
+                std::mem::swap(&mut population, &mut offspring);
+                ga.evolve_into(&mut rng, &population, &mut offspring);
+            }
+
+            assert_eq!(ga.pool.len(), 8);
+            assert_eq!(ga.pool.as_ptr(), pool);
+        }
+
+        #[test]
+        fn reuses_chromosomes() {
+            let mut ga = GeneticAlgorithm::new(
+                RouletteWheelSelection::new(),
+                UniformCrossover::new(),
+                GaussianMutation::new(0.5, 0.5),
+            );
+
+            let genes = |population: &[TestIndividual], offspring: &[TestIndividual]| {
+                let mut genes: Vec<_> = population
+                    .iter()
+                    .chain(offspring)
+                    .map(|individual| individual.chromosome().genes.as_ptr())
+                    .collect();
+
+                genes.sort_unstable();
+                genes
+            };
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut population = population();
+            let mut offspring = Vec::new();
+
+            ga.evolve_into(&mut rng, &population, &mut offspring);
+
+            let expected = genes(&population, &offspring);
+
+            for _ in 0..10 {
+                std::mem::swap(&mut population, &mut offspring);
+                ga.evolve_into(&mut rng, &population, &mut offspring);
+
+                assert_eq!(genes(&population, &offspring), expected);
+            }
+        }
+    }
+
+    mod try_evolve {
+        use super::*;
+
+        fn ga() -> GeneticAlgorithm<RouletteWheelSelection> {
+            GeneticAlgorithm::new(
+                RouletteWheelSelection::new(),
+                UniformCrossover::new(),
+                GaussianMutation::new(0.5, 0.5),
+            )
+        }
+
+        #[test]
+        fn rejects_empty_population() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population: Vec<TestIndividual> = vec![];
+
+            assert_eq!(
+                ga().try_evolve(&mut rng, &population),
+                Err(GeneticAlgorithmError::EmptyPopulation)
+            );
+        }
+
+        #[test]
+        fn rejects_mismatched_chromosomes() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = vec![individual(&[1.0, 2.0]), individual(&[1.0, 2.0, 3.0])];
+
+            assert_eq!(
+                ga().try_evolve(&mut rng, &population),
+                Err(GeneticAlgorithmError::ChromosomeLengthMismatch {
+                    expected: 2,
+                    actual: 3,
+                })
+            );
+        }
+
+        #[test]
+        fn evolves_valid_population() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = vec![individual(&[1.0, 2.0]), individual(&[3.0, 4.0])];
+
+            assert_eq!(ga().try_evolve(&mut rng, &population).unwrap().len(), 2);
+        }
+    }
 }
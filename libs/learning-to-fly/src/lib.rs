@@ -0,0 +1,83 @@
+pub use lib_genetic_algorithm as ga;
+pub use lib_neural_network as nn;
+
+use thiserror::Error;
+
+pub mod prelude {
+    pub use crate::Error;
+
+    pub use crate::ga::{
+        ArithmeticCrossover, BlendCrossover, BoltzmannSelection, Chromosome, CrossoverMethod,
+        GaussianMutation, GeneticAlgorithm, GeneticAlgorithmError, Individual, LexicaseSelection,
+        MultiPointCrossover, MutationMethod, RankSelection, RouletteWheelSelection,
+        SelectionMethod, StochasticUniversalSampling, TournamentSelection, UniformCrossover,
+    };
+
+    pub use crate::nn::{
+        Activation, Combine, Convolution, CustomLayer, Ensemble, FixedNetwork, Float, HalfNetwork,
+        Init, LayerKind, LayerTopology, LayerWeights, Network, NetworkBuilder, NetworkError,
+        NetworkState, Normalizer, PropagateBuffer, QuantizedNetwork, Sparsity, Trace,
+    };
+}
+
+/// Error of either crate, so that code using both can propagate their errors
+/// with `?`.
+#[derive(Clone, Debug, PartialEq, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Network(#[from] nn::NetworkError),
+
+    #[error(transparent)]
+    GeneticAlgorithm(#[from] ga::GeneticAlgorithmError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prelude::*;
+
+    mod error {
+        use super::*;
+
+        fn build(weights: &[Float], chance: f32) -> Result<(Network, GaussianMutation), Error> {
+            let builder = NetworkBuilder::new(1).output(1, Activation::Linear);
+            let network = Network::try_from_weights(builder.topology(), weights.iter().copied())?;
+
+            let mutation = GaussianMutation::try_new(chance, 0.5)?;
+
+            Ok((network, mutation))
+        }
+
+        #[test]
+        fn test() {
+            assert!(build(&[0.0, 1.0], 0.5).is_ok());
+        }
+
+        #[test]
+        fn from_network_error() {
+            assert_eq!(
+                build(&[0.0], 0.5).unwrap_err(),
+                Error::Network(NetworkError::WeightCountMismatch {
+                    expected: 2,
+                    actual: 1,
+                })
+            );
+        }
+
+        #[test]
+        fn from_genetic_algorithm_error() {
+            let err = build(&[0.0, 1.0], 2.0).unwrap_err();
+
+            assert_eq!(
+                err,
+                Error::GeneticAlgorithm(GeneticAlgorithmError::InvalidMutationChance {
+                    chance: 2.0
+                })
+            );
+
+            assert_eq!(
+                err.to_string(),
+                "mutation chance must be within 0.0..=1.0, got 2"
+            );
+        }
+    }
+}
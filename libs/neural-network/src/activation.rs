@@ -0,0 +1,256 @@
+use crate::Float;
+
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use crate::math::FloatExt;
+
+/// Function applied to a layer's outputs (i.e. to each neuron's sum of its
+/// bias and weighted inputs).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Activation {
+    /// `max(0, x)`
+    #[default]
+    Relu,
+
+    /// `x` for positive `x`, `alpha * x` otherwise - unlike [`Self::Relu`],
+    /// neurons stuck below zero still pass on (some) signal
+    LeakyRelu(f32),
+
+    /// Like [`Self::LeakyRelu`], but with `alpha` being a parameter of the
+    /// layer (see [`Self::PRELU_ALPHA`]), so that it evolves along with the
+    /// weights
+    Prelu,
+
+    /// `1 / (1 + e^-x)`, squashes into `(0, 1)`
+    Sigmoid,
+
+    /// Squashes into `(-1, 1)`
+    Tanh,
+
+    /// Passes the value through unchanged
+    Linear,
+
+    /// Turns the whole layer into a probability distribution (outputs are in
+    /// `(0, 1)` and sum up to 1), e.g. for picking one of N actions
+    Softmax,
+}
+
+impl Activation {
+    /// Initial `alpha` of [`Self::Prelu`] layers created by
+    /// [`Network::random()`](crate::Network::random).
+    pub const PRELU_ALPHA: f32 = 0.25;
+
+    /// Applies this activation; [`Self::Prelu`] uses [`Self::PRELU_ALPHA`].
+    pub fn apply(self, outputs: &mut [Float]) {
+        self.apply_with(outputs, Float::from(Self::PRELU_ALPHA));
+    }
+
+    /// Like [`Self::apply()`], but with given `alpha` for [`Self::Prelu`].
+    pub(crate) fn apply_with(self, outputs: &mut [Float], prelu_alpha: Float) {
+        match self {
+            Self::Relu => Self::map(outputs, |x| x.max(0.0)),
+            Self::LeakyRelu(alpha) => Self::leaky(outputs, Float::from(alpha)),
+            Self::Prelu => Self::leaky(outputs, prelu_alpha),
+            Self::Sigmoid => Self::map(outputs, |x| 1.0 / (1.0 + (-x).exp())),
+            Self::Tanh => Self::map(outputs, Float::tanh),
+            Self::Linear => (),
+            Self::Softmax => Self::softmax(outputs),
+        }
+    }
+
+    /// Turns gradients of the loss with respect to this activation's
+    /// `outputs` into gradients with respect to its inputs, in place.
+    ///
+    /// Leaky ReLU's `alpha` is assumed to be non-negative (so that outputs
+    /// have the same sign as inputs); PReLU isn't supported.
+    #[cfg(feature = "train")]
+    pub(crate) fn backpropagate(self, outputs: &[Float], grads: &mut [Float]) {
+        match self {
+            Self::Relu => Self::map_grads(outputs, grads, |y| if y > 0.0 { 1.0 } else { 0.0 }),
+
+            Self::LeakyRelu(alpha) => Self::map_grads(outputs, grads, |y| {
+                if y > 0.0 {
+                    1.0
+                } else {
+                    Float::from(alpha)
+                }
+            }),
+
+            Self::Prelu => unreachable!("PReLU can't be backpropagated"),
+            Self::Sigmoid => Self::map_grads(outputs, grads, |y| y * (1.0 - y)),
+            Self::Tanh => Self::map_grads(outputs, grads, |y| 1.0 - y * y),
+            Self::Linear => (),
+
+            Self::Softmax => {
+                let dot: Float = outputs.iter().zip(&*grads).map(|(y, g)| y * g).sum();
+
+                for (grad, y) in grads.iter_mut().zip(outputs) {
+                    *grad = y * (*grad - dot);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "train")]
+    fn map_grads(outputs: &[Float], grads: &mut [Float], derivative: impl Fn(Float) -> Float) {
+        for (grad, &y) in grads.iter_mut().zip(outputs) {
+            *grad *= derivative(y);
+        }
+    }
+
+    fn map(outputs: &mut [Float], f: impl Fn(Float) -> Float) {
+        for output in outputs {
+            *output = f(*output);
+        }
+    }
+
+    fn leaky(outputs: &mut [Float], alpha: Float) {
+        Self::map(outputs, |x| if x > 0.0 { x } else { alpha * x });
+    }
+
+    fn softmax(outputs: &mut [Float]) {
+        // Subtracting the maximum doesn't change the result, but keeps `exp()`
+        // from overflowing when the GA pushes weights to large values
+        let max = outputs
+            .iter()
+            .copied()
+            .fold(Float::NEG_INFINITY, Float::max);
+
+        Self::map(outputs, |x| (x - max).exp());
+
+        let sum: Float = outputs.iter().sum();
+
+        Self::map(outputs, |x| x / sum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TOLERANCE;
+    use approx::assert_relative_eq;
+
+    fn assert_applies(activation: Activation, outputs: &[Float], expected: &[Float]) {
+        let mut actual = outputs.to_vec();
+
+        activation.apply(&mut actual);
+
+        assert_relative_eq!(actual.as_slice(), expected, max_relative = TOLERANCE);
+    }
+
+    #[test]
+    fn relu() {
+        assert_applies(Activation::Relu, &[-2.0, 2.0], &[0.0, 2.0]);
+    }
+
+    #[test]
+    fn leaky_relu() {
+        assert_applies(Activation::LeakyRelu(0.1), &[-2.0, 2.0], &[-0.2, 2.0]);
+    }
+
+    #[test]
+    fn prelu() {
+        let mut actual = [-2.0, 2.0];
+
+        Activation::Prelu.apply_with(&mut actual, 0.5);
+
+        assert_eq!(actual, [-1.0, 2.0]);
+        assert_applies(Activation::Prelu, &[-2.0, 2.0], &[-0.5, 2.0]);
+    }
+
+    #[test]
+    fn sigmoid() {
+        assert_applies(
+            Activation::Sigmoid,
+            &[0.0, 2.0, -2.0],
+            &[0.5, 0.880797, 0.11920292],
+        );
+    }
+
+    #[test]
+    fn tanh() {
+        assert_applies(
+            Activation::Tanh,
+            &[0.0, 1.0, -1.0],
+            &[0.0, 0.7615942, -0.7615942],
+        );
+    }
+
+    #[test]
+    fn linear() {
+        assert_applies(Activation::Linear, &[-2.0, 2.0], &[-2.0, 2.0]);
+    }
+
+    #[cfg(feature = "train")]
+    mod backpropagate {
+        use super::*;
+
+        /// Compares against gradients estimated with finite differences
+        fn assert_backpropagates(activation: Activation) {
+            let inputs = [0.3, -0.2, 0.7];
+            let output_grads = [0.5, -1.0, 0.25];
+            let h = 1e-3;
+
+            let loss = |inputs: &[Float]| {
+                let mut outputs = inputs.to_vec();
+
+                activation.apply(&mut outputs);
+                outputs
+                    .iter()
+                    .zip(&output_grads)
+                    .map(|(y, g)| y * g)
+                    .sum::<Float>()
+            };
+
+            let mut outputs = inputs.to_vec();
+            let mut grads = output_grads.to_vec();
+
+            activation.apply(&mut outputs);
+            activation.backpropagate(&outputs, &mut grads);
+
+            for (n, grad) in grads.iter().enumerate() {
+                let (mut above, mut below) = (inputs, inputs);
+
+                above[n] += h;
+                below[n] -= h;
+
+                let expected = (loss(&above) - loss(&below)) / (2.0 * h);
+
+                assert_relative_eq!(*grad, expected, epsilon = 1e-2);
+            }
+        }
+
+        #[test]
+        fn test() {
+            for activation in [
+                Activation::Relu,
+                Activation::LeakyRelu(0.1),
+                Activation::Sigmoid,
+                Activation::Tanh,
+                Activation::Linear,
+                Activation::Softmax,
+            ] {
+                assert_backpropagates(activation);
+            }
+        }
+    }
+
+    mod softmax {
+        use super::*;
+
+        #[test]
+        fn test() {
+            assert_applies(
+                Activation::Softmax,
+                &[1.0, 2.0, 3.0],
+                &[0.09003057, 0.24472848, 0.66524094],
+            );
+        }
+
+        #[test]
+        fn handles_large_values() {
+            assert_applies(Activation::Softmax, &[1000.0, 1000.0], &[0.5, 0.5]);
+        }
+    }
+}
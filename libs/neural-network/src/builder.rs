@@ -0,0 +1,259 @@
+use crate::*;
+
+/// Fluent alternative to passing a `&[LayerTopology]` slice around, e.g.:
+///
+/// ```
+/// # use lib_neural_network::{Activation, NetworkBuilder};
+/// # use rand::SeedableRng;
+/// # let mut rng = rand_chacha::ChaCha8Rng::from_seed(Default::default());
+/// let network = NetworkBuilder::new(16)
+///     .layer(32)
+///     .activation(Activation::Tanh)
+///     .layer(8)
+///     .output(2, Activation::Sigmoid)
+///     .build(&mut rng);
+/// ```
+///
+/// Methods such as [`Self::activation()`] configure the most recently added
+/// layer.
+#[derive(Clone, Debug)]
+pub struct NetworkBuilder {
+    layers: Vec<LayerTopology>,
+}
+
+impl NetworkBuilder {
+    pub fn new(inputs: usize) -> Self {
+        Self {
+            layers: vec![LayerTopology {
+                neurons: inputs,
+                ..Default::default()
+            }],
+        }
+    }
+
+    /// Adds a dense ReLU layer.
+    pub fn layer(mut self, neurons: usize) -> Self {
+        self.layers.push(LayerTopology {
+            neurons,
+            ..Default::default()
+        });
+
+        self
+    }
+
+    /// Adds a convolutional ReLU layer, with as many neurons as the
+    /// convolution outputs.
+    pub fn convolution(mut self, convolution: Convolution) -> Self {
+        let inputs = self.layers[self.layers.len() - 1].neurons;
+
+        let neurons = convolution
+            .outputs(inputs)
+            .expect("previous layer's outputs don't form an image the kernel fits into");
+
+        self.layers.push(LayerTopology {
+            neurons,
+            kind: LayerKind::Convolution(convolution),
+            ..Default::default()
+        });
+
+        self
+    }
+
+    /// Adds the last layer; same as `.layer(neurons).activation(activation)`.
+    ///
+    /// The activation applies only to this layer, so e.g. steering outputs
+    /// can be bounded to `(-1, 1)` with [`Activation::Tanh`] while hidden
+    /// layers stay ReLU - no need to clamp them manually.
+    pub fn output(self, neurons: usize, activation: Activation) -> Self {
+        self.layer(neurons).activation(activation)
+    }
+
+    pub fn activation(mut self, activation: Activation) -> Self {
+        self.last().activation = activation;
+        self
+    }
+
+    pub fn kind(mut self, kind: LayerKind) -> Self {
+        self.last().kind = kind;
+        self
+    }
+
+    pub fn init(mut self, init: Init) -> Self {
+        self.last().init = init;
+        self
+    }
+
+    pub fn dropout(mut self, dropout: f32) -> Self {
+        self.last().dropout = dropout;
+        self
+    }
+
+    pub fn bias(mut self, bias: bool) -> Self {
+        self.last().bias = bias;
+        self
+    }
+
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.last().normalize = normalize;
+        self
+    }
+
+    /// Connects the layer's neurons only to some of its inputs, see
+    /// [`LayerTopology::mask`].
+    pub fn mask(mut self, mask: Vec<bool>) -> Self {
+        self.last().mask = Some(mask);
+        self
+    }
+
+    /// Adds a skip connection from given earlier layer (0 being the input
+    /// layer), see [`LayerTopology::skip`].
+    pub fn skip(mut self, from: usize) -> Self {
+        self.last().skip = Some(from);
+        self
+    }
+
+    pub fn topology(&self) -> &[LayerTopology] {
+        &self.layers
+    }
+
+    /// See: [`Network::random()`].
+    pub fn build(&self, rng: &mut dyn RngCore) -> Network {
+        Network::random(rng, &self.layers)
+    }
+
+    /// See: [`Network::random_with()`].
+    pub fn build_with(
+        &self,
+        rng: &mut dyn RngCore,
+        distribution: impl Distribution<f32>,
+    ) -> Network {
+        Network::random_with(rng, &self.layers, distribution)
+    }
+
+    /// See: [`Network::from_weights()`].
+    pub fn build_from_weights(&self, weights: impl IntoIterator<Item = Float>) -> Network {
+        Network::from_weights(&self.layers, weights)
+    }
+
+    fn last(&mut self) -> &mut LayerTopology {
+        assert!(
+            self.layers.len() > 1,
+            "the input layer can't be configured - call `.layer()` first"
+        );
+
+        self.layers.last_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod topology {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let builder = NetworkBuilder::new(3)
+                .layer(4)
+                .activation(Activation::Tanh)
+                .kind(LayerKind::Recurrent)
+                .init(Init::Xavier)
+                .output(2, Activation::Sigmoid);
+
+            let actual: Vec<_> = builder
+                .topology()
+                .iter()
+                .map(|layer| {
+                    (
+                        layer.neurons,
+                        layer.activation,
+                        layer.kind,
+                        layer.init.clone(),
+                    )
+                })
+                .collect();
+
+            let expected = vec![
+                (3, Activation::Relu, LayerKind::Dense, Init::default()),
+                (4, Activation::Tanh, LayerKind::Recurrent, Init::Xavier),
+                (2, Activation::Sigmoid, LayerKind::Dense, Init::default()),
+            ];
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        #[should_panic]
+        fn input_layer_cannot_be_configured() {
+            NetworkBuilder::new(3).activation(Activation::Tanh);
+        }
+    }
+
+    mod convolution {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let convolution = Convolution {
+                channels: 1,
+                width: 4,
+                kernel_width: 2,
+                kernel_height: 1,
+                filters: 2,
+            };
+
+            let builder = NetworkBuilder::new(4).convolution(convolution);
+            let layer = &builder.topology()[1];
+
+            assert_eq!(layer.neurons, 6);
+            assert_eq!(layer.kind, LayerKind::Convolution(convolution));
+        }
+    }
+
+    mod build {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test() {
+            let builder = NetworkBuilder::new(3)
+                .layer(2)
+                .output(1, Activation::Linear);
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let network = builder.build(&mut rng);
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let expected = Network::random(&mut rng, builder.topology());
+
+            assert!(network.weights().eq(expected.weights()));
+
+            let rebuilt = builder.build_from_weights(network.weights());
+
+            assert!(rebuilt.weights().eq(network.weights()));
+        }
+
+        #[test]
+        fn tanh_output() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let network = NetworkBuilder::new(2)
+                .layer(4)
+                .output(2, Activation::Tanh)
+                .build_with(&mut rng, rand_distr::Uniform::new(-10.0, 10.0));
+
+            let topology = network.topology();
+
+            assert_eq!(topology[1].activation, Activation::Relu);
+            assert_eq!(topology[2].activation, Activation::Tanh);
+
+            for inputs in [[1.0, 1.0], [-100.0, 50.0], [1000.0, -1000.0]] {
+                for output in network.propagate(inputs.to_vec()) {
+                    assert!((-1.0..=1.0).contains(&output), "{output}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,614 @@
+//! Compact binary checkpoints, see [`Network::save()`].
+//!
+//! Layout (all numbers little-endian):
+//!
+//! ```text
+//! magic       b"LTFN"
+//! version     u8, currently 1
+//! float size  u8, 4 or 8 - width of the floats that store parameters
+//! inputs      u32
+//! layers      u32
+//! for each layer:
+//!   neurons     u32
+//!   kind        u8 (see `KIND_*`), followed by five u32s for convolutions
+//!   activation  u8 (see `ACTIVATION_*`), followed by an f32 for leaky ReLU
+//!   flags       u8 (see `FLAG_*`)
+//!   skip        u32, present only with `FLAG_SKIP`
+//!   mask        bits of `LayerTopology::mask` packed into bytes (least
+//!               significant first), present only with `FLAG_MASK`
+//!   dropout     f32
+//! normalizer  u8 (0 or 1), followed by minimums and maximums of all inputs
+//! parameters  u32, followed by that many floats (see `Network::weights()`)
+//! ```
+//!
+//! Codes are assigned explicitly rather than derived from the enums, so that
+//! reordering variants doesn't break existing checkpoints; when the layout
+//! changes, `VERSION` gets bumped and older versions keep being read.
+
+use crate::*;
+use std::io;
+
+const MAGIC: &[u8; 4] = b"LTFN";
+const VERSION: u8 = 1;
+
+const KIND_DENSE: u8 = 0;
+const KIND_RECURRENT: u8 = 1;
+const KIND_LSTM: u8 = 2;
+const KIND_GRU: u8 = 3;
+const KIND_CONVOLUTION: u8 = 4;
+const KIND_PLASTIC: u8 = 5;
+
+const ACTIVATION_RELU: u8 = 0;
+const ACTIVATION_LEAKY_RELU: u8 = 1;
+const ACTIVATION_PRELU: u8 = 2;
+const ACTIVATION_SIGMOID: u8 = 3;
+const ACTIVATION_TANH: u8 = 4;
+const ACTIVATION_LINEAR: u8 = 5;
+const ACTIVATION_SOFTMAX: u8 = 6;
+
+const FLAG_BIAS: u8 = 1;
+const FLAG_NORMALIZE: u8 = 2;
+const FLAG_SKIP: u8 = 4;
+const FLAG_MASK: u8 = 8;
+
+impl Network {
+    /// Writes this network's topology, normalizer and parameters into
+    /// `writer`, so that [`Self::load()`] can restore it - even when saved by
+    /// an older build of this crate.
+    ///
+    /// State of recurrent layers, training mode and [`LayerTopology::init`]
+    /// don't get saved; networks containing custom layers can't be saved at
+    /// all.
+    pub fn save(&self, mut writer: impl io::Write) -> io::Result<()> {
+        let topology = self.topology();
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(core::mem::size_of::<Float>() as u8);
+        put_usize(&mut bytes, self.input_size())?;
+        put_usize(&mut bytes, self.layers.len())?;
+
+        for layer in &topology[1..] {
+            put_usize(&mut bytes, layer.neurons)?;
+
+            match layer.kind {
+                LayerKind::Dense => bytes.push(KIND_DENSE),
+                LayerKind::Recurrent => bytes.push(KIND_RECURRENT),
+                LayerKind::Lstm => bytes.push(KIND_LSTM),
+                LayerKind::Gru => bytes.push(KIND_GRU),
+                LayerKind::Plastic => bytes.push(KIND_PLASTIC),
+
+                LayerKind::Convolution(convolution) => {
+                    bytes.push(KIND_CONVOLUTION);
+
+                    for value in [
+                        convolution.channels,
+                        convolution.width,
+                        convolution.kernel_width,
+                        convolution.kernel_height,
+                        convolution.filters,
+                    ] {
+                        put_usize(&mut bytes, value)?;
+                    }
+                }
+
+                LayerKind::Custom => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "custom layers can't be saved",
+                    ))
+                }
+            }
+
+            match layer.activation {
+                Activation::Relu => bytes.push(ACTIVATION_RELU),
+
+                Activation::LeakyRelu(alpha) => {
+                    bytes.push(ACTIVATION_LEAKY_RELU);
+                    bytes.extend_from_slice(&alpha.to_le_bytes());
+                }
+
+                Activation::Prelu => bytes.push(ACTIVATION_PRELU),
+                Activation::Sigmoid => bytes.push(ACTIVATION_SIGMOID),
+                Activation::Tanh => bytes.push(ACTIVATION_TANH),
+                Activation::Linear => bytes.push(ACTIVATION_LINEAR),
+                Activation::Softmax => bytes.push(ACTIVATION_SOFTMAX),
+            }
+
+            let mut flags = 0;
+
+            if layer.bias {
+                flags |= FLAG_BIAS;
+            }
+
+            if layer.normalize {
+                flags |= FLAG_NORMALIZE;
+            }
+
+            if layer.skip.is_some() {
+                flags |= FLAG_SKIP;
+            }
+
+            if layer.mask.is_some() {
+                flags |= FLAG_MASK;
+            }
+
+            bytes.push(flags);
+
+            if let Some(skip) = layer.skip {
+                put_usize(&mut bytes, skip)?;
+            }
+
+            if let Some(mask) = &layer.mask {
+                for bits in mask.chunks(8) {
+                    bytes.push(
+                        bits.iter()
+                            .enumerate()
+                            .map(|(bit, &connected)| u8::from(connected) << bit)
+                            .sum(),
+                    );
+                }
+            }
+
+            bytes.extend_from_slice(&layer.dropout.to_le_bytes());
+        }
+
+        match &self.normalizer {
+            Some(normalizer) => {
+                bytes.push(1);
+
+                for value in normalizer.min().iter().chain(normalizer.max()) {
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+
+            None => bytes.push(0),
+        }
+
+        put_usize(&mut bytes, self.num_parameters())?;
+
+        for weight in self.weights() {
+            bytes.extend_from_slice(&weight.to_le_bytes());
+        }
+
+        writer.write_all(&bytes)
+    }
+
+    /// Reads a network written by [`Self::save()`].
+    ///
+    /// Checkpoints store parameters with the precision of the build that
+    /// saved them; loading one saved with the `f64` feature into a build
+    /// without it rounds them to `f32` (and vice versa, losslessly).
+    pub fn load(mut reader: impl io::Read) -> io::Result<Self> {
+        let reader = &mut reader;
+
+        if &take::<4>(reader)? != MAGIC {
+            return Err(invalid("not a network checkpoint"));
+        }
+
+        match take_u8(reader)? {
+            1 => (),
+            version => {
+                return Err(invalid(format!(
+                    "unsupported checkpoint version {version}, expected at most {VERSION}"
+                )))
+            }
+        }
+
+        let float_size = take_u8(reader)?;
+
+        if float_size != 4 && float_size != 8 {
+            return Err(invalid(format!("unsupported float size {float_size}")));
+        }
+
+        let mut topology = vec![LayerTopology {
+            neurons: take_usize(reader)?,
+            ..Default::default()
+        }];
+
+        for _ in 0..take_usize(reader)? {
+            let neurons = take_usize(reader)?;
+
+            let kind = match take_u8(reader)? {
+                KIND_DENSE => LayerKind::Dense,
+                KIND_RECURRENT => LayerKind::Recurrent,
+                KIND_LSTM => LayerKind::Lstm,
+                KIND_GRU => LayerKind::Gru,
+                KIND_PLASTIC => LayerKind::Plastic,
+
+                KIND_CONVOLUTION => LayerKind::Convolution(Convolution {
+                    channels: take_usize(reader)?,
+                    width: take_usize(reader)?,
+                    kernel_width: take_usize(reader)?,
+                    kernel_height: take_usize(reader)?,
+                    filters: take_usize(reader)?,
+                }),
+
+                kind => return Err(invalid(format!("unknown layer kind {kind}"))),
+            };
+
+            let activation = match take_u8(reader)? {
+                ACTIVATION_RELU => Activation::Relu,
+                ACTIVATION_LEAKY_RELU => Activation::LeakyRelu(f32::from_le_bytes(take(reader)?)),
+                ACTIVATION_PRELU => Activation::Prelu,
+                ACTIVATION_SIGMOID => Activation::Sigmoid,
+                ACTIVATION_TANH => Activation::Tanh,
+                ACTIVATION_LINEAR => Activation::Linear,
+                ACTIVATION_SOFTMAX => Activation::Softmax,
+
+                activation => return Err(invalid(format!("unknown activation {activation}"))),
+            };
+
+            let flags = take_u8(reader)?;
+
+            let skip = if flags & FLAG_SKIP != 0 {
+                Some(take_usize(reader)?)
+            } else {
+                None
+            };
+
+            let mask = if flags & FLAG_MASK != 0 {
+                let len = neurons
+                    .checked_mul(topology[topology.len() - 1].neurons)
+                    .ok_or_else(|| invalid("mask is too large"))?;
+
+                Some(take_mask(reader, len)?)
+            } else {
+                None
+            };
+
+            topology.push(LayerTopology {
+                neurons,
+                activation,
+                kind,
+                dropout: f32::from_le_bytes(take(reader)?),
+                skip,
+                bias: flags & FLAG_BIAS != 0,
+                normalize: flags & FLAG_NORMALIZE != 0,
+                mask,
+                ..Default::default()
+            });
+        }
+
+        Self::validate(&topology).map_err(invalid)?;
+
+        let inputs = topology[0].neurons;
+
+        let normalizer = match take_u8(reader)? {
+            0 => None,
+
+            1 => {
+                let min = take_floats(reader, float_size, inputs)?;
+                let max = take_floats(reader, float_size, inputs)?;
+
+                Some(Normalizer::new(min, max))
+            }
+
+            normalizer => return Err(invalid(format!("unknown normalizer {normalizer}"))),
+        };
+
+        let expected = Self::num_parameters_of(&topology);
+        let actual = take_usize(reader)?;
+
+        if actual != expected {
+            return Err(invalid(NetworkError::WeightCountMismatch {
+                expected,
+                actual,
+            }));
+        }
+
+        let weights = take_floats(reader, float_size, expected)?;
+        let mut network = Self::try_from_weights(&topology, weights).map_err(invalid)?;
+
+        network.normalizer = normalizer;
+
+        Ok(network)
+    }
+}
+
+fn put_usize(bytes: &mut Vec<u8>, value: usize) -> io::Result<()> {
+    let value = u32::try_from(value)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "network is too large"))?;
+
+    bytes.extend_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+fn take<const N: usize>(reader: &mut impl io::Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn take_u8(reader: &mut impl io::Read) -> io::Result<u8> {
+    Ok(take::<1>(reader)?[0])
+}
+
+fn take_usize(reader: &mut impl io::Read) -> io::Result<usize> {
+    Ok(u32::from_le_bytes(take(reader)?) as usize)
+}
+
+/// Reads `len` floats of given size, converting them into [`Float`]s.
+fn take_floats(reader: &mut impl io::Read, size: u8, len: usize) -> io::Result<Vec<Float>> {
+    // Collected one by one rather than into a preallocated vector, so that a
+    // corrupted length fails on the end of file instead of allocating it all
+    (0..len)
+        .map(|_| match size {
+            4 => Ok(f32::from_le_bytes(take(reader)?) as Float),
+            _ => Ok(f64::from_le_bytes(take(reader)?) as Float),
+        })
+        .collect()
+}
+
+/// Reads a mask of `len` bits packed into bytes, see [`Network::save()`].
+fn take_mask(reader: &mut impl io::Read, len: usize) -> io::Result<Vec<bool>> {
+    // Read byte by byte for the same reason as in `take_floats()`
+    let mut mask = Vec::new();
+
+    for _ in 0..len.div_ceil(8) {
+        let byte = take_u8(reader)?;
+
+        mask.extend((0..8).map(|bit| byte & (1 << bit) != 0));
+    }
+
+    mask.truncate(len);
+    Ok(mask)
+}
+
+fn invalid(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn roundtrip(network: &Network) -> Network {
+        let mut bytes = Vec::new();
+
+        network.save(&mut bytes).unwrap();
+        Network::load(bytes.as_slice()).unwrap()
+    }
+
+    mod roundtrip {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let mut network = NetworkBuilder::new(2)
+                .layer(2)
+                .activation(Activation::LeakyRelu(0.1))
+                .dropout(0.25)
+                .bias(false)
+                .layer(2)
+                .activation(Activation::Prelu)
+                .normalize(true)
+                .skip(1)
+                .layer(3)
+                .kind(LayerKind::Gru)
+                .layer(2)
+                .kind(LayerKind::Plastic)
+                .output(2, Activation::Softmax)
+                .build(&mut rng);
+
+            network.set_normalizer(Some(Normalizer::new(vec![0.0, -1.0], vec![1.0, 1.0])));
+
+            let actual = roundtrip(&network);
+
+            assert_eq!(actual, network);
+            assert_eq!(actual.topology(), network.topology());
+        }
+
+        #[test]
+        fn mask() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mask: Vec<_> = (0..3 * 4).map(|index| index % 5 != 0).collect();
+
+            let network = NetworkBuilder::new(3)
+                .layer(4)
+                .mask(mask)
+                .output(1, Activation::Sigmoid)
+                .build(&mut rng);
+
+            let actual = roundtrip(&network);
+
+            assert_eq!(actual, network);
+            assert_eq!(actual.topology(), network.topology());
+        }
+
+        #[test]
+        fn convolution() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let convolution = Convolution {
+                channels: 1,
+                width: 3,
+                kernel_width: 2,
+                kernel_height: 1,
+                filters: 2,
+            };
+
+            let network = NetworkBuilder::new(3)
+                .layer(4)
+                .kind(LayerKind::Convolution(convolution))
+                .output(1, Activation::Sigmoid)
+                .build(&mut rng);
+
+            assert_eq!(roundtrip(&network), network);
+        }
+    }
+
+    mod load {
+        use super::*;
+
+        /// Checkpoint of a `2 -> 1` sigmoid network with weights `0.5, -1.0,
+        /// 0.25`, as written by version 1 with `f32`s; has to keep loading
+        /// as the format evolves.
+        const VERSION_1: &[u8] = &[
+            b'L', b'T', b'F', b'N', 1, 4, // magic, version, float size
+            2, 0, 0, 0, // inputs
+            1, 0, 0, 0, // layers
+            1, 0, 0, 0, // neurons
+            0, 3, 1, // kind, activation, flags
+            0, 0, 0, 0, // dropout
+            0, // normalizer
+            3, 0, 0, 0, // parameters
+            0, 0, 0, 0x3f, // 0.5
+            0, 0, 0x80, 0xbf, // -1.0
+            0, 0, 0x80, 0x3e, // 0.25
+        ];
+
+        #[test]
+        fn version_1() {
+            let network = Network::load(VERSION_1).unwrap();
+
+            let expected = NetworkBuilder::new(2)
+                .output(1, Activation::Sigmoid)
+                .build_from_weights([0.5, -1.0, 0.25]);
+
+            assert_eq!(network, expected);
+        }
+
+        #[cfg(not(feature = "f64"))]
+        #[test]
+        fn saves_version_1() {
+            let mut bytes = Vec::new();
+
+            Network::load(VERSION_1).unwrap().save(&mut bytes).unwrap();
+
+            assert_eq!(bytes, VERSION_1);
+        }
+
+        #[test]
+        fn rejects_wrong_magic() {
+            let err = Network::load(&b"GIF89a"[..]).unwrap_err();
+
+            assert_eq!(err.to_string(), "not a network checkpoint");
+        }
+
+        #[test]
+        fn rejects_newer_versions() {
+            let mut bytes = VERSION_1.to_vec();
+
+            bytes[4] = 2;
+
+            assert_eq!(
+                Network::load(bytes.as_slice()).unwrap_err().to_string(),
+                "unsupported checkpoint version 2, expected at most 1"
+            );
+        }
+
+        #[test]
+        fn rejects_truncated_checkpoints() {
+            let err = Network::load(&VERSION_1[..VERSION_1.len() - 1]).unwrap_err();
+
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+
+        #[test]
+        fn rejects_invalid_topologies() {
+            let mut bytes = VERSION_1.to_vec();
+
+            bytes[10] = 0;
+
+            assert_eq!(
+                Network::load(bytes.as_slice()).unwrap_err().to_string(),
+                "a network needs at least 2 layers (input and output), got 1"
+            );
+        }
+
+        /// Header of a checkpoint with `u32::MAX` inputs and a single layer
+        /// of `u32::MAX` neurons, up to (and including) the layer's kind.
+        fn huge(kind: u8) -> Vec<u8> {
+            [
+                &[b'L', b'T', b'F', b'N', 1, 4][..], // magic, version, float size
+                &[0xff; 4],                          // inputs
+                &[1, 0, 0, 0],                       // layers
+                &[0xff; 4],                          // neurons
+                &[kind],
+            ]
+            .concat()
+        }
+
+        #[test]
+        fn rejects_huge_layers() {
+            let mut bytes = huge(KIND_GRU);
+
+            bytes.extend([ACTIVATION_TANH, 0, 0, 0, 0, 0, 0]);
+
+            assert_eq!(
+                Network::load(bytes.as_slice()).unwrap_err().to_string(),
+                "layer #0 has too many parameters"
+            );
+        }
+
+        #[test]
+        fn rejects_huge_masks() {
+            let mut bytes = huge(KIND_DENSE);
+
+            bytes.extend([ACTIVATION_SIGMOID, FLAG_MASK, 0xff, 0xff]);
+
+            assert!(Network::load(bytes.as_slice()).is_err());
+        }
+
+        #[test]
+        fn rejects_huge_convolutions() {
+            let mut bytes = huge(KIND_CONVOLUTION);
+
+            bytes.extend([0xff; 5 * 4]);
+            bytes.extend([ACTIVATION_RELU, 0, 0, 0, 0, 0, 0]);
+
+            assert_eq!(
+                Network::load(bytes.as_slice()).unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+
+        /// Corrupts random bytes of a checkpoint, which must either load or
+        /// fail - but never panic nor try to allocate absurd amounts of
+        /// memory.
+        #[test]
+        fn survives_corruption() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mask: Vec<_> = (0..2 * 3).map(|index| index != 4).collect();
+
+            let network = NetworkBuilder::new(2)
+                .layer(3)
+                .mask(mask)
+                .layer(2)
+                .kind(LayerKind::Lstm)
+                .layer(4)
+                .kind(LayerKind::Convolution(Convolution {
+                    channels: 1,
+                    width: 2,
+                    kernel_width: 1,
+                    kernel_height: 1,
+                    filters: 2,
+                }))
+                .output(1, Activation::Sigmoid)
+                .build(&mut rng);
+
+            let mut checkpoint = Vec::new();
+
+            network.save(&mut checkpoint).unwrap();
+
+            for _ in 0..10_000 {
+                let mut bytes = checkpoint.clone();
+
+                for _ in 0..rng.gen_range(1..=4) {
+                    let index = rng.gen_range(0..bytes.len());
+
+                    bytes[index] = if rng.gen_bool(0.5) { 0xff } else { rng.gen() };
+                }
+
+                let _ = Network::load(bytes.as_slice());
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use crate::*;
+
+/// Networks are equal when they have the same topology (see
+/// [`Network::topology()`]), normalizer and parameters; whether the networks
+/// are in training mode or add noise to their inputs doesn't matter.
+impl PartialEq for Network {
+    fn eq(&self, other: &Self) -> bool {
+        self.topology() == other.topology()
+            && self.normalizer == other.normalizer
+            && self.weights().eq(other.weights())
+    }
+}
+
+/// Compares parameters one by one, see [`PartialEq`] for what else has to
+/// match.
+#[cfg(feature = "approx")]
+impl approx::AbsDiffEq for Network {
+    type Epsilon = Float;
+
+    fn default_epsilon() -> Float {
+        Float::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Float) -> bool {
+        self.topology() == other.topology()
+            && self.normalizer == other.normalizer
+            && self
+                .weights()
+                .zip(other.weights())
+                .all(|(a, b)| a.abs_diff_eq(&b, epsilon))
+    }
+}
+
+#[cfg(feature = "approx")]
+impl approx::RelativeEq for Network {
+    fn default_max_relative() -> Float {
+        Float::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: Float, max_relative: Float) -> bool {
+        self.topology() == other.topology()
+            && self.normalizer == other.normalizer
+            && self
+                .weights()
+                .zip(other.weights())
+                .all(|(a, b)| a.relative_eq(&b, epsilon, max_relative))
+    }
+}
+
+/// Layers are equal when they have the same shape and kind (see
+/// [`Layer::topology()`]) and parameters; custom layers have to be of the same
+/// type as well (judging by [`CustomLayer::name()`]), while the state of
+/// stateful layers lives elsewhere and doesn't matter.
+impl PartialEq for Layer {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_shape(other) && self.weights().eq(other.weights())
+    }
+}
+
+/// Compares parameters one by one, see [`PartialEq`] for what else has to
+/// match.
+#[cfg(feature = "approx")]
+impl approx::AbsDiffEq for Layer {
+    type Epsilon = Float;
+
+    fn default_epsilon() -> Float {
+        Float::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Float) -> bool {
+        self.same_shape(other)
+            && self
+                .weights()
+                .zip(other.weights())
+                .all(|(a, b)| a.abs_diff_eq(&b, epsilon))
+    }
+}
+
+#[cfg(feature = "approx")]
+impl approx::RelativeEq for Layer {
+    fn default_max_relative() -> Float {
+        Float::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: Float, max_relative: Float) -> bool {
+        self.same_shape(other)
+            && self
+                .weights()
+                .zip(other.weights())
+                .all(|(a, b)| a.relative_eq(&b, epsilon, max_relative))
+    }
+}
+
+impl Layer {
+    fn same_shape(&self, other: &Self) -> bool {
+        self.inputs() == other.inputs()
+            && self.name() == other.name()
+            && self.topology() == other.topology()
+    }
+}
+
+/// Dense layers are equal when they have the same shape, activation, flags
+/// (bias, normalization), mask and parameters.
+impl PartialEq for DenseLayer {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_shape(other) && self.weights().eq(other.weights())
+    }
+}
+
+/// Compares parameters one by one, see [`PartialEq`] for what else has to
+/// match.
+#[cfg(feature = "approx")]
+impl approx::AbsDiffEq for DenseLayer {
+    type Epsilon = Float;
+
+    fn default_epsilon() -> Float {
+        Float::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Float) -> bool {
+        self.same_shape(other)
+            && self
+                .weights()
+                .zip(other.weights())
+                .all(|(a, b)| a.abs_diff_eq(&b, epsilon))
+    }
+}
+
+#[cfg(feature = "approx")]
+impl approx::RelativeEq for DenseLayer {
+    fn default_max_relative() -> Float {
+        Float::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: Float, max_relative: Float) -> bool {
+        self.same_shape(other)
+            && self
+                .weights()
+                .zip(other.weights())
+                .all(|(a, b)| a.relative_eq(&b, epsilon, max_relative))
+    }
+}
+
+impl DenseLayer {
+    fn same_shape(&self, other: &Self) -> bool {
+        self.inputs() == other.inputs()
+            && self.outputs() == other.outputs()
+            && self.activation() == other.activation()
+            && self.has_bias() == other.has_bias()
+            && self.normalizes() == other.normalizes()
+            && self.mask() == other.mask()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(weights: [Float; 3]) -> Network {
+        NetworkBuilder::new(2)
+            .output(1, Activation::Linear)
+            .build_from_weights(weights)
+    }
+
+    mod eq {
+        use super::*;
+
+        #[test]
+        fn test() {
+            assert_eq!(network([0.5, -1.0, 0.25]), network([0.5, -1.0, 0.25]));
+            assert_ne!(network([0.5, -1.0, 0.25]), network([0.5, -1.0, 0.26]));
+        }
+
+        #[test]
+        fn compares_topologies() {
+            let a = network([0.0; 3]);
+
+            let b = NetworkBuilder::new(2)
+                .output(1, Activation::Sigmoid)
+                .build_from_weights([0.0; 3]);
+
+            assert_ne!(a, b);
+        }
+
+        #[test]
+        fn compares_normalizers() {
+            let a = network([0.0; 3]);
+            let mut b = a.clone();
+
+            b.set_normalizer(Some(Normalizer::new(vec![0.0; 2], vec![1.0; 2])));
+
+            assert_ne!(a, b);
+        }
+    }
+
+    mod layer {
+        use super::*;
+
+        fn dense(weights: [Float; 3]) -> DenseLayer {
+            DenseLayer::new(
+                vec![weights[0]],
+                vec![weights[1..].to_vec()],
+                Activation::Linear,
+            )
+        }
+
+        #[test]
+        fn test() {
+            assert_eq!(dense([0.5, -1.0, 0.25]), dense([0.5, -1.0, 0.25]));
+            assert_ne!(dense([0.5, -1.0, 0.25]), dense([0.5, -1.0, 0.26]));
+
+            assert_eq!(
+                Layer::Dense(dense([0.5, -1.0, 0.25])),
+                Layer::Dense(dense([0.5, -1.0, 0.25]))
+            );
+
+            assert_ne!(
+                Layer::Dense(dense([0.5, -1.0, 0.25])),
+                Layer::Dense(dense([0.5, -1.0, 0.26]))
+            );
+        }
+
+        #[test]
+        fn compares_shapes() {
+            let a = dense([0.0; 3]);
+            let b = DenseLayer::new(vec![0.0], vec![vec![0.0; 2]], Activation::Sigmoid);
+
+            assert_ne!(a, b);
+            assert_ne!(Layer::Dense(a.clone()), Layer::Dense(b));
+
+            // Same parameters, but not the same kind of layer
+            let recurrent = NetworkBuilder::new(1)
+                .output(1, Activation::Linear)
+                .kind(LayerKind::Recurrent)
+                .build_from_weights([0.0; 3]);
+
+            assert_ne!(Layer::Dense(a), recurrent.layers[0]);
+        }
+
+        #[cfg(feature = "approx")]
+        #[test]
+        fn approx() {
+            use ::approx::{assert_abs_diff_eq, assert_relative_eq, assert_relative_ne};
+
+            let a = dense([0.5, -1.0, 0.25]);
+            let b = dense([0.5, -1.0, 0.2500001]);
+
+            assert_abs_diff_eq!(a, b, epsilon = 1e-6);
+            assert_relative_eq!(a, b, max_relative = 1e-6);
+            assert_relative_ne!(a, dense([0.5, -1.0, 0.26]));
+
+            assert_relative_eq!(Layer::Dense(a), Layer::Dense(b), max_relative = 1e-6);
+        }
+    }
+
+    #[cfg(feature = "approx")]
+    mod approx {
+        use super::*;
+        use ::approx::{assert_abs_diff_eq, assert_relative_eq, assert_relative_ne};
+
+        #[test]
+        fn test() {
+            let a = network([0.5, -1.0, 0.25]);
+            let b = network([0.5, -1.0, 0.2500001]);
+
+            assert_abs_diff_eq!(a, b, epsilon = 1e-6);
+            assert_relative_eq!(a, b, max_relative = 1e-6);
+            assert_relative_ne!(a, network([0.5, -1.0, 0.26]));
+        }
+    }
+}
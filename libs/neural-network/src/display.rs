@@ -0,0 +1,132 @@
+use crate::*;
+use core::fmt;
+
+/// Summarizes the network layer by layer, e.g.:
+///
+/// ```text
+/// network: 2 inputs, 1 outputs, 9 parameters
+///   #0: dense (tanh), 2 -> 2, 6 parameters, min -0.500, max 1.000, mean 0.250
+///   #1: dense (sigmoid), 2 -> 1, 3 parameters, min 0.000, max 2.000, mean 1.000
+/// ```
+///
+/// Statistics cover each layer's biases and weights together.
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "network: {} inputs, {} outputs, {} parameters",
+            self.input_size(),
+            self.output_size(),
+            self.num_parameters(),
+        )?;
+
+        if self.normalizer.is_some() {
+            write!(f, ", normalized inputs")?;
+        }
+
+        for (id, layer) in self.layers.iter().enumerate() {
+            let topology = layer.topology();
+
+            write!(f, "\n  #{id}: {}", layer.name())?;
+
+            if !matches!(layer, Layer::Lstm(_) | Layer::Gru(_) | Layer::Custom(_)) {
+                write!(
+                    f,
+                    " ({})",
+                    format!("{:?}", topology.activation).to_lowercase()
+                )?;
+            }
+
+            write!(
+                f,
+                ", {} -> {}, {} parameters",
+                layer.inputs(),
+                layer.outputs(),
+                layer.num_weights(),
+            )?;
+
+            if layer.num_weights() > 0 {
+                let (min, max, sum) = layer.weights().fold(
+                    (Float::INFINITY, Float::NEG_INFINITY, 0.0),
+                    |(min, max, sum), weight| (min.min(weight), max.max(weight), sum + weight),
+                );
+
+                write!(
+                    f,
+                    ", min {min:.3}, max {max:.3}, mean {:.3}",
+                    sum / layer.num_weights() as Float,
+                )?;
+            }
+
+            if topology.normalize {
+                write!(f, ", normalized")?;
+            }
+
+            match self.skip(id) {
+                Some(0) => write!(f, ", skip from input")?,
+                Some(from) => write!(f, ", skip from #{}", from - 1)?,
+                None => (),
+            }
+
+            let dropout = self.dropout_rate(id);
+
+            if dropout > 0.0 {
+                write!(f, ", dropout {dropout}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn dense() {
+        let network = NetworkBuilder::new(2)
+            .layer(2)
+            .activation(Activation::Tanh)
+            .dropout(0.25)
+            .output(1, Activation::Sigmoid)
+            .build_from_weights([0.0, 1.0, -0.5, 0.5, 0.5, 0.0, 0.0, 1.0, 2.0]);
+
+        assert_eq!(
+            network.to_string(),
+            "network: 2 inputs, 1 outputs, 9 parameters\n  \
+             #0: dense (tanh), 2 -> 2, 6 parameters, min -0.500, max 1.000, mean 0.250, dropout 0.25\n  \
+             #1: dense (sigmoid), 2 -> 1, 3 parameters, min 0.000, max 2.000, mean 1.000"
+        );
+    }
+
+    #[test]
+    fn skip() {
+        let network = NetworkBuilder::new(2)
+            .layer(2)
+            .skip(0)
+            .layer(2)
+            .skip(1)
+            .build_from_weights([0.0; 12]);
+
+        let display = network.to_string();
+
+        assert!(display.contains("#0: dense (relu), 2 -> 2, 6 parameters, min 0.000, max 0.000, mean 0.000, skip from input\n"));
+        assert!(display.ends_with(", skip from #0"));
+    }
+
+    #[test]
+    fn lstm() {
+        let network = NetworkBuilder::new(1)
+            .layer(1)
+            .kind(LayerKind::Lstm)
+            .build_from_weights([0.5; 12]);
+
+        assert_eq!(
+            network.to_string(),
+            "network: 1 inputs, 1 outputs, 12 parameters\n  \
+             #0: LSTM, 1 -> 1, 12 parameters, min 0.500, max 0.500, mean 0.500"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Dot product used by neurons; with the `simd` feature it's computed 256
+//! bits (i.e. eight `f32` or four `f64` lanes) at a time.
+//!
+//! Both paths accumulate in exactly the same order (lane-wise sums, which are
+//! then added up left to right, followed by the remainder), so enabling the
+//! feature doesn't change any outputs - not even in the last bit - and brains
+//! evolved with one build behave identically in the other.
+
+use crate::Float;
+
+/// Amount of floats that fit into 256 bits
+#[cfg(not(feature = "f64"))]
+const LANES: usize = 8;
+
+#[cfg(feature = "f64")]
+const LANES: usize = 4;
+
+pub fn dot(a: &[Float], b: &[Float]) -> Float {
+    assert_eq!(a.len(), b.len());
+
+    #[cfg(feature = "simd")]
+    return simd::dot(a, b);
+
+    #[cfg(not(feature = "simd"))]
+    return scalar::dot(a, b);
+}
+
+fn reduce(lanes: [Float; LANES], a: &[Float], b: &[Float]) -> Float {
+    let sum = lanes.iter().fold(0.0, |sum, lane| sum + lane);
+
+    a.iter().zip(b).fold(sum, |sum, (a, b)| sum + a * b)
+}
+
+#[cfg_attr(feature = "simd", allow(dead_code))]
+mod scalar {
+    use super::*;
+
+    pub fn dot(a: &[Float], b: &[Float]) -> Float {
+        let chunks_a = a.chunks_exact(LANES);
+        let chunks_b = b.chunks_exact(LANES);
+        let (rest_a, rest_b) = (chunks_a.remainder(), chunks_b.remainder());
+        let mut lanes = [0.0; LANES];
+
+        for (a, b) in chunks_a.zip(chunks_b) {
+            for ((lane, a), b) in lanes.iter_mut().zip(a).zip(b) {
+                *lane += a * b;
+            }
+        }
+
+        reduce(lanes, rest_a, rest_b)
+    }
+}
+
+#[cfg(feature = "simd")]
+mod simd {
+    use super::*;
+
+    #[cfg(not(feature = "f64"))]
+    use wide::f32x8 as Lanes;
+
+    #[cfg(feature = "f64")]
+    use wide::f64x4 as Lanes;
+
+    pub fn dot(a: &[Float], b: &[Float]) -> Float {
+        let chunks_a = a.chunks_exact(LANES);
+        let chunks_b = b.chunks_exact(LANES);
+        let (rest_a, rest_b) = (chunks_a.remainder(), chunks_b.remainder());
+        let mut lanes = Lanes::ZERO;
+
+        for (a, b) in chunks_a.zip(chunks_b) {
+            let a = Lanes::from(<[Float; LANES]>::try_from(a).unwrap());
+            let b = Lanes::from(<[Float; LANES]>::try_from(b).unwrap());
+
+            lanes += a * b;
+        }
+
+        reduce(lanes.to_array(), rest_a, rest_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use approx::assert_relative_eq;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn vectors(len: usize) -> (Vec<Float>, Vec<Float>) {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let a = (0..len).map(|_| rng.gen_range(-1.0..=1.0)).collect();
+        let b = (0..len).map(|_| rng.gen_range(-1.0..=1.0)).collect();
+
+        (a, b)
+    }
+
+    #[test]
+    fn test() {
+        for len in [0, 1, 7, 8, 9, 31, 100] {
+            let (a, b) = vectors(len);
+            let expected: Float = a.iter().zip(&b).map(|(a, b)| a * b).sum();
+
+            assert_relative_eq!(dot(&a, &b), expected, epsilon = 1e-5);
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_matches_scalar() {
+        for len in [0, 1, 7, 8, 9, 31, 100, 2048] {
+            let (a, b) = vectors(len);
+
+            assert_eq!(simd::dot(&a, &b).to_bits(), scalar::dot(&a, &b).to_bits());
+        }
+    }
+}
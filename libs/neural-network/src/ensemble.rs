@@ -0,0 +1,263 @@
+//! Ensembles of networks, e.g. for deploying the best few brains of an
+//! evolution together.
+
+use crate::*;
+
+/// Group of networks with the same input and output sizes that get
+/// propagated together, their outputs combined into one - a single network
+/// may have evolved a quirk, but several of them rarely share it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ensemble {
+    networks: Vec<Network>,
+    combine: Combine,
+}
+
+/// How an [`Ensemble`] combines outputs of its networks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Combine {
+    /// Averages each output across the networks, e.g. for continuous control
+    #[default]
+    Mean,
+
+    /// Lets each network vote for its largest output (the first one on a
+    /// tie) and returns the fraction of votes each output got, e.g. for
+    /// picking one of several actions
+    Vote,
+}
+
+impl Ensemble {
+    /// Panics if there are no networks or if their sizes differ.
+    pub fn new(networks: Vec<Network>, combine: Combine) -> Self {
+        match Self::try_new(networks, combine) {
+            Ok(ensemble) => ensemble,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Like [`Self::new()`], but returns an error instead of panicking.
+    pub fn try_new(networks: Vec<Network>, combine: Combine) -> Result<Self, NetworkError> {
+        let first = networks.first().ok_or(NetworkError::EmptyEnsemble)?;
+
+        for network in &networks[1..] {
+            if network.input_size() != first.input_size() {
+                return Err(NetworkError::InputSizeMismatch {
+                    expected: first.input_size(),
+                    actual: network.input_size(),
+                });
+            }
+
+            if network.output_size() != first.output_size() {
+                return Err(NetworkError::OutputSizeMismatch {
+                    expected: first.output_size(),
+                    actual: network.output_size(),
+                });
+            }
+        }
+
+        Ok(Self { networks, combine })
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.networks[0].input_size()
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.networks[0].output_size()
+    }
+
+    pub fn networks(&self) -> &[Network] {
+        &self.networks
+    }
+
+    pub fn combine(&self) -> Combine {
+        self.combine
+    }
+
+    /// Propagates `inputs` through each network and combines their outputs.
+    pub fn propagate(&self, inputs: Vec<Float>) -> Vec<Float> {
+        self.combined(|id, outputs| {
+            outputs.copy_from_slice(&self.networks[id].propagate(inputs.clone()));
+        })
+    }
+
+    /// Like [`Self::propagate()`], but each network continues from its own
+    /// state, see [`Network::propagate_with()`].
+    ///
+    /// Panics if `states` don't come from [`Self::state()`].
+    pub fn propagate_with(&self, states: &mut [NetworkState], inputs: Vec<Float>) -> Vec<Float> {
+        assert_eq!(states.len(), self.networks.len());
+
+        let mut buffer = PropagateBuffer::new();
+
+        self.combined(|id, outputs| {
+            self.networks[id].propagate_into_with(&mut states[id], &inputs, outputs, &mut buffer);
+        })
+    }
+
+    /// Returns the state of each network, see [`Network::state()`].
+    pub fn state(&self) -> Vec<NetworkState> {
+        self.networks.iter().map(Network::state).collect()
+    }
+
+    /// Combines outputs that `propagate` writes for each network.
+    fn combined(&self, mut propagate: impl FnMut(usize, &mut [Float])) -> Vec<Float> {
+        let mut outputs = vec![0.0; self.output_size()];
+        let mut network_outputs = vec![0.0; self.output_size()];
+
+        for id in 0..self.networks.len() {
+            propagate(id, &mut network_outputs);
+
+            match self.combine {
+                Combine::Mean => {
+                    for (output, network_output) in outputs.iter_mut().zip(&network_outputs) {
+                        *output += network_output;
+                    }
+                }
+
+                Combine::Vote => {
+                    let vote = (0..network_outputs.len())
+                        .reduce(|best, id| {
+                            if network_outputs[id] > network_outputs[best] {
+                                id
+                            } else {
+                                best
+                            }
+                        })
+                        .unwrap();
+
+                    outputs[vote] += 1.0;
+                }
+            }
+        }
+
+        let len = self.networks.len() as Float;
+
+        for output in &mut outputs {
+            *output /= len;
+        }
+
+        outputs
+    }
+
+    /// Like [`Self::propagate()`], but returns an error instead of panicking
+    /// when `inputs` doesn't match the size of the input layer.
+    pub fn try_propagate(&self, inputs: Vec<Float>) -> Result<Vec<Float>, NetworkError> {
+        let expected = self.input_size();
+
+        if inputs.len() != expected {
+            return Err(NetworkError::InputSizeMismatch {
+                expected,
+                actual: inputs.len(),
+            });
+        }
+
+        Ok(self.propagate(inputs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    /// Linear network passing its single input scaled by `weights`
+    fn network(weights: [Float; 2]) -> Network {
+        NetworkBuilder::new(1)
+            .output(2, Activation::Linear)
+            .bias(false)
+            .build_from_weights(weights)
+    }
+
+    fn ensemble(combine: Combine) -> Ensemble {
+        Ensemble::new(
+            vec![
+                network([1.0, 2.0]),
+                network([3.0, 0.0]),
+                network([2.0, 4.0]),
+            ],
+            combine,
+        )
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn mean() {
+            let actual = ensemble(Combine::Mean).propagate(vec![1.5]);
+
+            assert_relative_eq!(actual.as_slice(), [3.0, 3.0].as_ref());
+        }
+
+        #[test]
+        fn vote() {
+            let actual = ensemble(Combine::Vote).propagate(vec![1.0]);
+
+            assert_relative_eq!(
+                actual.as_slice(),
+                [1.0 / 3.0, 2.0 / 3.0].as_ref(),
+                max_relative = TOLERANCE
+            );
+        }
+
+        #[test]
+        fn with_state() {
+            let recurrent = || {
+                NetworkBuilder::new(1)
+                    .output(1, Activation::Linear)
+                    .kind(LayerKind::Recurrent)
+                    .build_from_weights([0.0, 1.0, 1.0])
+            };
+
+            let ensemble = Ensemble::new(vec![recurrent(), recurrent()], Combine::Mean);
+            let mut states = ensemble.state();
+
+            // Each network adds its input to its previous output
+            for expected in [1.0, 2.0, 3.0] {
+                assert_eq!(ensemble.propagate_with(&mut states, vec![1.0]), [expected]);
+            }
+
+            assert_eq!(ensemble.propagate(vec![1.0]), [1.0]);
+        }
+
+        #[test]
+        fn rejects_wrong_input_size() {
+            assert_eq!(
+                ensemble(Combine::Mean).try_propagate(vec![1.0, 2.0]),
+                Err(NetworkError::InputSizeMismatch {
+                    expected: 1,
+                    actual: 2,
+                })
+            );
+        }
+    }
+
+    mod try_new {
+        use super::*;
+
+        #[test]
+        fn rejects_no_networks() {
+            assert_eq!(
+                Ensemble::try_new(Vec::new(), Combine::Mean).unwrap_err(),
+                NetworkError::EmptyEnsemble
+            );
+        }
+
+        #[test]
+        fn rejects_different_sizes() {
+            let other = NetworkBuilder::new(1)
+                .output(3, Activation::Linear)
+                .build_from_weights([0.0; 6]);
+
+            assert_eq!(
+                Ensemble::try_new(vec![network([1.0, 2.0]), other], Combine::Mean).unwrap_err(),
+                NetworkError::OutputSizeMismatch {
+                    expected: 2,
+                    actual: 3,
+                }
+            );
+        }
+    }
+}
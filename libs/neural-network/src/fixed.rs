@@ -0,0 +1,240 @@
+//! Networks with a topology fixed at compile time, e.g. for small brains
+//! propagated in the hot loop of a simulation.
+
+use crate::*;
+
+/// Inference-only copy of a [`Network`] with `IN` inputs, a single hidden
+/// layer of `HIDDEN` neurons and `OUT` outputs, see [`Network::to_fixed()`].
+///
+/// Parameters live in arrays instead of vectors, so the whole network can sit
+/// on the stack (or inline in another struct), and propagation neither
+/// allocates nor has to check sizes at runtime; outputs are the same as of
+/// the original network, down to the last bit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedNetwork<const IN: usize, const HIDDEN: usize, const OUT: usize> {
+    /// Offsets and factors of the network's [`Normalizer`], if any
+    normalizer: Option<[(Float, Float); IN]>,
+    hidden: FixedLayer<IN, HIDDEN>,
+    output: FixedLayer<HIDDEN, OUT>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct FixedLayer<const IN: usize, const OUT: usize> {
+    biases: [Float; OUT],
+    weights: [[Float; IN]; OUT],
+    activation: Activation,
+    prelu_alpha: Float,
+    normalize: bool,
+}
+
+impl Network {
+    /// Converts this network into a [`FixedNetwork`].
+    ///
+    /// Panics if the network doesn't consist of exactly two dense layers
+    /// (hidden and output) without skip connections, or if their sizes
+    /// don't match `IN`, `HIDDEN` and `OUT`.
+    pub fn to_fixed<const IN: usize, const HIDDEN: usize, const OUT: usize>(
+        &self,
+    ) -> FixedNetwork<IN, HIDDEN, OUT> {
+        match self.try_to_fixed() {
+            Ok(network) => network,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Like [`Self::to_fixed()`], but returns an error instead of panicking.
+    pub fn try_to_fixed<const IN: usize, const HIDDEN: usize, const OUT: usize>(
+        &self,
+    ) -> Result<FixedNetwork<IN, HIDDEN, OUT>, NetworkError> {
+        if self.input_size() != IN {
+            return Err(NetworkError::InputSizeMismatch {
+                expected: IN,
+                actual: self.input_size(),
+            });
+        }
+
+        if self.output_size() != OUT {
+            return Err(NetworkError::OutputSizeMismatch {
+                expected: OUT,
+                actual: self.output_size(),
+            });
+        }
+
+        if self.layers.len() != 2 || self.layers[0].outputs() != HIDDEN {
+            return Err(NetworkError::TopologyMismatch);
+        }
+
+        let dense = |id: usize| match &self.layers[id] {
+            Layer::Dense(layer) if self.skip(id).is_none() => Ok(layer),
+            _ => Err(NetworkError::UnsupportedLayer { layer: id }),
+        };
+
+        let (hidden, output) = (dense(0)?, dense(1)?);
+
+        let normalizer = self.normalizer.as_ref().map(|normalizer| {
+            let mut scales = normalizer.scales();
+
+            core::array::from_fn(|_| scales.next().unwrap())
+        });
+
+        Ok(FixedNetwork {
+            normalizer,
+            hidden: FixedLayer::new(hidden),
+            output: FixedLayer::new(output),
+        })
+    }
+}
+
+impl<const IN: usize, const HIDDEN: usize, const OUT: usize> FixedNetwork<IN, HIDDEN, OUT> {
+    /// Same as [`Network::propagate()`].
+    pub fn propagate(&self, inputs: &[Float; IN]) -> [Float; OUT] {
+        let mut inputs = *inputs;
+
+        if let Some(normalizer) = &self.normalizer {
+            for (input, (offset, factor)) in inputs.iter_mut().zip(normalizer) {
+                *input = (factor * (*input - offset)).clamp(0.0, 1.0);
+            }
+        }
+
+        self.output.propagate(&self.hidden.propagate(&inputs))
+    }
+}
+
+impl<const IN: usize, const OUT: usize> FixedLayer<IN, OUT> {
+    fn new(layer: &DenseLayer) -> Self {
+        Self {
+            biases: core::array::from_fn(|neuron| layer.bias(neuron).unwrap_or(0.0)),
+            weights: core::array::from_fn(|neuron| layer.row(neuron).try_into().unwrap()),
+            activation: layer.activation(),
+            prelu_alpha: layer.prelu_alpha(),
+            normalize: layer.normalizes(),
+        }
+    }
+
+    fn propagate(&self, inputs: &[Float; IN]) -> [Float; OUT] {
+        let mut outputs: [Float; OUT] =
+            core::array::from_fn(|neuron| self.biases[neuron] + dot(inputs, &self.weights[neuron]));
+
+        if self.normalize {
+            DenseLayer::normalize(&mut outputs);
+        }
+
+        self.activation.apply_with(&mut outputs, self.prelu_alpha);
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let network = NetworkBuilder::new(9)
+                .layer(16)
+                .activation(Activation::Tanh)
+                .output(2, Activation::Sigmoid)
+                .build(&mut rng);
+
+            let fixed = network.to_fixed::<9, 16, 2>();
+            let inputs: [Float; 9] = core::array::from_fn(|i| i as Float / 9.0 - 0.5);
+
+            assert_eq!(
+                fixed.propagate(&inputs).to_vec(),
+                network.propagate(inputs.to_vec())
+            );
+        }
+
+        #[test]
+        fn bias_normalization_and_normalizer() {
+            let mut network = NetworkBuilder::new(2)
+                .layer(3)
+                .bias(false)
+                .normalize(true)
+                .output(1, Activation::Prelu)
+                .build_from_weights([0.1, -0.2, 0.3, 0.4, 0.5, -0.6, 0.7, 0.8, 0.9, 1.0, 0.25]);
+
+            network.set_normalizer(Some(Normalizer::new(vec![0.0, -1.0], vec![2.0, 1.0])));
+
+            let fixed = network.to_fixed::<2, 3, 1>();
+
+            for inputs in [[1.0, 0.5], [-3.0, 3.0], [0.25, -0.75]] {
+                assert_eq!(
+                    fixed.propagate(&inputs).to_vec(),
+                    network.propagate(inputs.to_vec())
+                );
+            }
+        }
+    }
+
+    mod try_to_fixed {
+        use super::*;
+
+        fn network(kind: LayerKind) -> Network {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            NetworkBuilder::new(2)
+                .layer(3)
+                .kind(kind)
+                .output(1, Activation::Linear)
+                .build(&mut rng)
+        }
+
+        #[test]
+        fn rejects_wrong_sizes() {
+            let network = network(LayerKind::Dense);
+
+            assert_eq!(
+                network.try_to_fixed::<3, 3, 1>().unwrap_err(),
+                NetworkError::InputSizeMismatch {
+                    expected: 3,
+                    actual: 2
+                }
+            );
+
+            assert_eq!(
+                network.try_to_fixed::<2, 3, 2>().unwrap_err(),
+                NetworkError::OutputSizeMismatch {
+                    expected: 2,
+                    actual: 1
+                }
+            );
+
+            assert_eq!(
+                network.try_to_fixed::<2, 4, 1>().unwrap_err(),
+                NetworkError::TopologyMismatch
+            );
+        }
+
+        #[test]
+        fn rejects_recurrent_layers() {
+            assert_eq!(
+                network(LayerKind::Recurrent)
+                    .try_to_fixed::<2, 3, 1>()
+                    .unwrap_err(),
+                NetworkError::UnsupportedLayer { layer: 0 }
+            );
+        }
+
+        #[test]
+        fn rejects_skip_connections() {
+            let network = NetworkBuilder::new(2)
+                .layer(2)
+                .output(2, Activation::Linear)
+                .skip(1)
+                .build_from_weights([0.0; 12]);
+
+            assert_eq!(
+                network.try_to_fixed::<2, 2, 2>().unwrap_err(),
+                NetworkError::UnsupportedLayer { layer: 1 }
+            );
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::*;
+use core::fmt::Write;
+
+impl Network {
+    /// Renders this network as a Graphviz graph (e.g. for `dot -Tsvg`), with
+    /// one node per neuron - labelled with its bias - grouped into a cluster
+    /// per layer.
+    ///
+    /// Weights of dense and recurrent layers are drawn as edges labelled with
+    /// their value, blue when positive and red when negative, thicker the
+    /// larger their magnitude; other layers' weights don't map to single
+    /// connections, so their clusters are connected by a single edge instead.
+    /// Skip connections are drawn as dashed edges between clusters as well.
+    pub fn to_dot(&self) -> String {
+        let max = self
+            .weights()
+            .map(Float::abs)
+            .fold(Float::EPSILON, Float::max);
+
+        let mut dot = String::new();
+
+        writeln!(dot, "digraph network {{").unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    compound=true;").unwrap();
+        writeln!(dot, "    node [shape=circle];").unwrap();
+
+        Self::write_cluster(&mut dot, 0, "input", self.input_size(), |_| String::new());
+
+        for (id, layer) in self.layers.iter().enumerate() {
+            let topology = layer.topology();
+            let activation = format!("{:?}", topology.activation).to_lowercase();
+
+            let label = match topology.kind {
+                LayerKind::Lstm | LayerKind::Gru | LayerKind::Custom => layer.name().into(),
+                _ => format!("{}, {activation}", layer.name()),
+            };
+
+            let column = id + 1;
+
+            match layer {
+                Layer::Dense(_) | Layer::Recurrent(_) => {
+                    let params = layer.params();
+
+                    Self::write_cluster(&mut dot, column, &label, topology.neurons, |neuron| {
+                        format!("{:.2}", params.bias(neuron).unwrap())
+                    });
+
+                    for neuron in 0..topology.neurons {
+                        for (input, &weight) in params.row(neuron).iter().enumerate() {
+                            if !params.is_connected(neuron, input) {
+                                continue;
+                            }
+
+                            // Recurrent layers' rows continue with weights for
+                            // the layer's own previous outputs
+                            let source = if input < layer.inputs() {
+                                (column - 1, input)
+                            } else {
+                                (column, input - layer.inputs())
+                            };
+
+                            writeln!(
+                                dot,
+                                "    n{}_{} -> n{column}_{neuron} [label=\"{weight:.2}\", color={}, penwidth={:.2}];",
+                                source.0,
+                                source.1,
+                                if weight < 0.0 { "red" } else { "blue" },
+                                0.5 + 2.5 * weight.abs() / max,
+                            )
+                            .unwrap();
+                        }
+                    }
+                }
+
+                _ => {
+                    Self::write_cluster(&mut dot, column, &label, topology.neurons, |_| {
+                        String::new()
+                    });
+
+                    writeln!(
+                        dot,
+                        "    n{}_0 -> n{column}_0 [ltail=cluster_{}, lhead=cluster_{column}, label=\"{} weights\"];",
+                        column - 1,
+                        column - 1,
+                        layer.num_weights(),
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        for id in 0..self.layers.len() {
+            if let Some(from) = self.skip(id) {
+                writeln!(
+                    dot,
+                    "    n{from}_0 -> n{}_0 [ltail=cluster_{from}, lhead=cluster_{}, style=dashed, label=\"skip\"];",
+                    id + 1,
+                    id + 1,
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+
+        dot
+    }
+
+    fn write_cluster(
+        dot: &mut String,
+        column: usize,
+        label: &str,
+        neurons: usize,
+        neuron_label: impl Fn(usize) -> String,
+    ) {
+        writeln!(dot, "    subgraph cluster_{column} {{").unwrap();
+        writeln!(dot, "        label=\"{label}\";").unwrap();
+
+        for neuron in 0..neurons {
+            writeln!(
+                dot,
+                "        n{column}_{neuron} [label=\"{}\"];",
+                neuron_label(neuron)
+            )
+            .unwrap();
+        }
+
+        writeln!(dot, "    }}").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense() {
+        let network = Network::new(vec![Layer::Dense(DenseLayer::new(
+            vec![0.1],
+            vec![vec![0.5, -1.0]],
+            Activation::Sigmoid,
+        ))]);
+
+        let dot = network.to_dot();
+
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.contains("        label=\"dense, sigmoid\";\n"));
+        assert!(dot.contains("        n1_0 [label=\"0.10\"];\n"));
+        assert!(dot.contains("    n0_0 -> n1_0 [label=\"0.50\", color=blue, penwidth=1.75];\n"));
+        assert!(dot.contains("    n0_1 -> n1_0 [label=\"-1.00\", color=red, penwidth=3.00];\n"));
+    }
+
+    #[test]
+    fn recurrent() {
+        let network = NetworkBuilder::new(1)
+            .layer(1)
+            .kind(LayerKind::Recurrent)
+            .build_from_weights([0.0, 1.0, 0.5]);
+
+        assert!(network
+            .to_dot()
+            .contains("    n1_0 -> n1_0 [label=\"0.50\", color=blue, penwidth=1.75];\n"));
+    }
+
+    #[test]
+    fn skip() {
+        let network = NetworkBuilder::new(1)
+            .layer(1)
+            .layer(1)
+            .skip(0)
+            .build_from_weights([0.0; 4]);
+
+        assert!(network.to_dot().contains(
+            "    n0_0 -> n2_0 [ltail=cluster_0, lhead=cluster_2, style=dashed, label=\"skip\"];\n"
+        ));
+    }
+
+    #[test]
+    fn lstm() {
+        let network = NetworkBuilder::new(1)
+            .layer(1)
+            .kind(LayerKind::Lstm)
+            .build_from_weights([0.0; 12]);
+
+        assert!(network.to_dot().contains(
+            "    n0_0 -> n1_0 [ltail=cluster_0, lhead=cluster_1, label=\"12 weights\"];\n"
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! Half-precision storage, e.g. for keeping tens of thousands of brains in
+//! WASM memory.
+
+use crate::*;
+use ::half::f16;
+
+/// Inference-only copy of a [`Network`] with biases and weights stored as
+/// IEEE 754 half-precision floats, see [`Network::to_half()`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HalfNetwork {
+    layers: Vec<HalfLayer>,
+    skips: Vec<Option<usize>>,
+    normalizer: Option<Normalizer>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct HalfLayer {
+    inputs: usize,
+    biases: Vec<f16>,
+    weights: Vec<f16>,
+    activation: Activation,
+    prelu_alpha: Float,
+    normalize: bool,
+}
+
+impl Network {
+    /// Converts this network into a [`HalfNetwork`], which stores its biases
+    /// and weights in half the memory (a quarter with the `f64` feature) and
+    /// upcasts them while propagating; that keeps about three significant
+    /// digits, so outputs differ from the original network's slightly.
+    ///
+    /// Panics if there are layers other than dense ones.
+    pub fn to_half(&self) -> HalfNetwork {
+        match self.try_to_half() {
+            Ok(network) => network,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Like [`Self::to_half()`], but returns an error instead of panicking.
+    pub fn try_to_half(&self) -> Result<HalfNetwork, NetworkError> {
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(id, layer)| match layer {
+                Layer::Dense(layer) => Ok(HalfLayer::new(layer)),
+                _ => Err(NetworkError::UnsupportedLayer { layer: id }),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(HalfNetwork {
+            layers,
+            skips: (0..self.layers.len()).map(|id| self.skip(id)).collect(),
+            normalizer: self.normalizer.clone(),
+        })
+    }
+}
+
+impl HalfNetwork {
+    /// Same as [`Network::propagate()`].
+    pub fn propagate(&self, inputs: Vec<Float>) -> Vec<Float> {
+        let mut activations = vec![inputs];
+
+        if let Some(normalizer) = &self.normalizer {
+            normalizer.normalize(&mut activations[0]);
+        }
+
+        for (layer, skip) in self.layers.iter().zip(&self.skips) {
+            let skip = skip.map(|from| activations[from].as_slice());
+            let outputs = layer.propagate(&activations[activations.len() - 1], skip);
+
+            activations.push(outputs);
+        }
+
+        activations.pop().unwrap()
+    }
+}
+
+impl HalfLayer {
+    fn new(layer: &DenseLayer) -> Self {
+        Self {
+            inputs: layer.inputs(),
+            biases: (0..layer.outputs())
+                .map(|neuron| Self::to_f16(layer.bias(neuron).unwrap()))
+                .collect(),
+            weights: (0..layer.outputs())
+                .flat_map(|neuron| layer.row(neuron))
+                .map(|&weight| Self::to_f16(weight))
+                .collect(),
+            activation: layer.activation(),
+            prelu_alpha: layer.prelu_alpha(),
+            normalize: layer.normalizes(),
+        }
+    }
+
+    fn to_f16(value: Float) -> f16 {
+        // Widening to f64 is lossless, so it rounds just once
+        #[allow(clippy::unnecessary_cast)]
+        f16::from_f64(value as f64)
+    }
+
+    /// Propagates `inputs`, adding `skip` (if any) to the weighted sums
+    /// before the activation.
+    fn propagate(&self, inputs: &[Float], skip: Option<&[Float]>) -> Vec<Float> {
+        assert_eq!(inputs.len(), self.inputs);
+
+        let mut outputs: Vec<_> = (0..self.biases.len())
+            .map(|neuron| {
+                let sum: Float = self.weights[neuron * self.inputs..][..self.inputs]
+                    .iter()
+                    .zip(inputs)
+                    .map(|(weight, input)| Float::from(*weight) * input)
+                    .sum();
+
+                Float::from(self.biases[neuron]) + sum
+            })
+            .collect();
+
+        if let Some(skip) = skip {
+            for (output, skip) in outputs.iter_mut().zip(skip) {
+                *output += skip;
+            }
+        }
+
+        if self.normalize {
+            DenseLayer::normalize(&mut outputs);
+        }
+
+        self.activation.apply_with(&mut outputs, self.prelu_alpha);
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let network = NetworkBuilder::new(8)
+                .layer(16)
+                .activation(Activation::Tanh)
+                .output(4, Activation::Sigmoid)
+                .build(&mut rng);
+
+            let half = network.to_half();
+            let inputs: Vec<_> = (0..8).map(|i| i as Float / 8.0 - 0.5).collect();
+
+            let expected = network.propagate(inputs.clone());
+            let actual = half.propagate(inputs);
+
+            assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 0.005);
+        }
+
+        #[test]
+        fn rounds_to_nearest_half() {
+            let network = NetworkBuilder::new(1)
+                .output(2, Activation::Linear)
+                .build_from_weights([0.0, 0.1, 0.0, 65520.0]);
+
+            // 0.1 isn't a half, 65520 is too large for one
+            let actual = network.to_half().propagate(vec![1.0]);
+
+            assert_eq!(actual, [1638.0 / 16384.0, Float::INFINITY]);
+        }
+
+        #[test]
+        fn skip_and_normalizer() {
+            let mut network = NetworkBuilder::new(2)
+                .layer(2)
+                .skip(0)
+                .output(2, Activation::Linear)
+                .skip(0)
+                .build_from_weights([[0.0; 6], [0.0, 1.0, 0.0, 0.0, 0.0, 1.0]].concat());
+
+            network.set_normalizer(Some(Normalizer::new(vec![0.0, 0.0], vec![2.0, 4.0])));
+
+            let expected = network.propagate(vec![1.0, 1.0]);
+            let actual = network.to_half().propagate(vec![1.0, 1.0]);
+
+            assert_eq!(actual, expected);
+        }
+    }
+
+    mod try_to_half {
+        use super::*;
+
+        #[test]
+        fn rejects_recurrent_layers() {
+            let network = NetworkBuilder::new(1)
+                .layer(1)
+                .kind(LayerKind::Recurrent)
+                .build_from_weights([0.0; 3]);
+
+            assert_eq!(
+                network.try_to_half().unwrap_err(),
+                NetworkError::UnsupportedLayer { layer: 0 }
+            );
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::Float;
+use core::ops::RangeInclusive;
+use rand::{Rng, RngCore};
+use rand_distr::{Distribution, Normal};
+
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use crate::math::FloatExt;
+
+/// Distribution a layer's parameters (biases and weights) are drawn from by
+/// [`Network::random()`](crate::Network::random).
+///
+/// Values are always drawn as `f32`, so that seeded networks come out the same
+/// regardless of the `f64` feature.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Init {
+    /// Uniformly from given range
+    Uniform(RangeInclusive<f32>),
+
+    /// Uniformly from `±sqrt(6 / (inputs + outputs))` (Glorot & Bengio),
+    /// keeps sigmoid and tanh layers from saturating as they grow wider
+    Xavier,
+
+    /// Normally with a mean of 0 and a standard deviation of
+    /// `sqrt(2 / inputs)` (He et al.), Xavier's counterpart for ReLU layers
+    He,
+
+    /// Normally with given mean and standard deviation
+    Normal { mean: f32, std: f32 },
+}
+
+impl Default for Init {
+    fn default() -> Self {
+        Self::Uniform(-1.0..=1.0)
+    }
+}
+
+impl Init {
+    /// Returns a function drawing parameters for a layer of given size.
+    pub(crate) fn sampler<'a>(
+        &self,
+        rng: &'a mut dyn RngCore,
+        inputs: usize,
+        outputs: usize,
+    ) -> impl FnMut() -> Float + 'a {
+        let sampler = match self {
+            Self::Uniform(range) => Sampler::Uniform(range.clone()),
+
+            Self::Xavier => {
+                let limit = (6.0 / (inputs + outputs).max(1) as f32).sqrt();
+
+                Sampler::Uniform(-limit..=limit)
+            }
+
+            Self::He => Sampler::normal(0.0, (2.0 / inputs.max(1) as f32).sqrt()),
+            Self::Normal { mean, std } => Sampler::normal(*mean, *std),
+        };
+
+        move || {
+            Float::from(match &sampler {
+                Sampler::Uniform(range) => rng.gen_range(range.clone()),
+                Sampler::Normal(normal) => normal.sample(&mut *rng),
+            })
+        }
+    }
+}
+
+enum Sampler {
+    Uniform(RangeInclusive<f32>),
+    Normal(Normal<f32>),
+}
+
+impl Sampler {
+    fn normal(mean: f32, std: f32) -> Self {
+        Self::Normal(Normal::new(mean, std).expect("std must be finite and non-negative"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn sample(init: Init, inputs: usize, outputs: usize) -> Vec<Float> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut sampler = init.sampler(&mut rng, inputs, outputs);
+
+        (0..10_000).map(|_| sampler()).collect()
+    }
+
+    fn mean_and_std(values: &[Float]) -> (Float, Float) {
+        let len = values.len() as Float;
+        let mean = values.iter().sum::<Float>() / len;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<Float>() / len;
+
+        (mean, variance.sqrt())
+    }
+
+    #[test]
+    fn uniform() {
+        let values = sample(Init::Uniform(0.5..=1.5), 1, 1);
+
+        assert!(values.iter().all(|x| (0.5..=1.5).contains(x)));
+    }
+
+    #[test]
+    fn xavier() {
+        let values = sample(Init::Xavier, 20, 4);
+        let limit = 0.5;
+
+        assert!(values.iter().all(|x| (-limit..=limit).contains(x)));
+        assert!(values.iter().any(|x| x.abs() > 0.9 * limit));
+    }
+
+    #[test]
+    fn he() {
+        let (mean, std) = mean_and_std(&sample(Init::He, 8, 1));
+
+        assert_relative_eq!(mean, 0.0, epsilon = 0.02);
+        assert_relative_eq!(std, 0.5, epsilon = 0.02);
+    }
+
+    #[test]
+    fn normal() {
+        let (mean, std) = mean_and_std(&sample(
+            Init::Normal {
+                mean: 3.0,
+                std: 2.0,
+            },
+            1,
+            1,
+        ));
+
+        assert_relative_eq!(mean, 3.0, epsilon = 0.05);
+        assert_relative_eq!(std, 2.0, epsilon = 0.05);
+    }
+}
@@ -0,0 +1,356 @@
+mod convolution;
+mod custom;
+mod dense;
+mod gru;
+mod lstm;
+mod plastic;
+mod recurrent;
+
+pub use self::{convolution::*, custom::*, dense::*, gru::*, lstm::*, plastic::*, recurrent::*};
+use crate::*;
+use alloc::boxed::Box;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Layer {
+    Dense(DenseLayer),
+    Recurrent(RecurrentLayer),
+    Lstm(LstmLayer),
+    Gru(GruLayer),
+    Convolution(ConvolutionLayer),
+    Plastic(PlasticLayer),
+
+    /// Can't be (de)serialized, since there's no telling which type to
+    /// deserialize it into
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Box<dyn CustomLayer>),
+}
+
+/// Memory of a stateful layer, see [`NetworkState`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayerState {
+    /// The layer doesn't remember anything between calls
+    None,
+
+    Recurrent(RecurrentState),
+    Lstm(LstmState),
+    Gru(GruState),
+    Plastic(PlasticState),
+
+    /// See [`CustomLayer::state_size()`]
+    Custom(Vec<Float>),
+}
+
+impl LayerState {
+    /// Forgets everything the layer remembered.
+    pub fn reset(&mut self) {
+        match self {
+            Self::None => (),
+            Self::Recurrent(state) => state.reset(),
+            Self::Lstm(state) => state.reset(),
+            Self::Gru(state) => state.reset(),
+            Self::Plastic(state) => state.reset(),
+            Self::Custom(state) => state.fill(0.0),
+        }
+    }
+}
+
+impl Layer {
+    pub fn random(rng: &mut dyn RngCore, inputs: usize, topology: &LayerTopology) -> Self {
+        match topology.kind {
+            LayerKind::Dense => Self::Dense(DenseLayer::random(rng, inputs, topology)),
+            LayerKind::Recurrent => Self::Recurrent(RecurrentLayer::random(rng, inputs, topology)),
+            LayerKind::Lstm => Self::Lstm(LstmLayer::random(rng, inputs, topology)),
+            LayerKind::Gru => Self::Gru(GruLayer::random(rng, inputs, topology)),
+            LayerKind::Plastic => Self::Plastic(PlasticLayer::random(rng, inputs, topology)),
+
+            LayerKind::Convolution(convolution) => {
+                Self::Convolution(ConvolutionLayer::random(rng, inputs, topology, convolution))
+            }
+
+            LayerKind::Custom => Self::custom_kind(),
+        }
+    }
+
+    pub fn from_weights(
+        inputs: usize,
+        topology: &LayerTopology,
+        weights: &mut dyn Iterator<Item = Float>,
+    ) -> Self {
+        match topology.kind {
+            LayerKind::Dense => Self::Dense(DenseLayer::from_weights(inputs, topology, weights)),
+
+            LayerKind::Recurrent => {
+                Self::Recurrent(RecurrentLayer::from_weights(inputs, topology, weights))
+            }
+
+            LayerKind::Lstm => Self::Lstm(LstmLayer::from_weights(inputs, topology, weights)),
+            LayerKind::Gru => Self::Gru(GruLayer::from_weights(inputs, topology, weights)),
+
+            LayerKind::Plastic => {
+                Self::Plastic(PlasticLayer::from_weights(inputs, topology, weights))
+            }
+
+            LayerKind::Convolution(convolution) => Self::Convolution(
+                ConvolutionLayer::from_weights(inputs, topology, convolution, weights),
+            ),
+
+            LayerKind::Custom => Self::custom_kind(),
+        }
+    }
+
+    /// Returns how many weights [`Self::from_weights()`] consumes.
+    pub fn num_parameters(inputs: usize, topology: &LayerTopology) -> usize {
+        Self::checked_num_parameters(inputs, topology).expect("layer has too many parameters")
+    }
+
+    /// Like [`Self::num_parameters()`], but returns `None` instead of
+    /// overflowing, e.g. for topologies read from untrusted checkpoints.
+    pub(crate) fn checked_num_parameters(inputs: usize, topology: &LayerTopology) -> Option<usize> {
+        let neurons = topology.neurons;
+        let bias = usize::from(topology.bias);
+        let alpha = usize::from(topology.activation == Activation::Prelu);
+
+        // Weights of layers that see their own outputs besides their inputs
+        let recurrent = || neurons.checked_mul(bias.checked_add(inputs)?.checked_add(neurons)?);
+
+        match topology.kind {
+            LayerKind::Dense => {
+                let weights = match &topology.mask {
+                    Some(mask) => mask.iter().filter(|&&connected| connected).count(),
+                    None => neurons.checked_mul(inputs)?,
+                };
+
+                (neurons * bias).checked_add(weights)?.checked_add(alpha)
+            }
+
+            LayerKind::Recurrent => recurrent()?.checked_add(alpha),
+            LayerKind::Lstm => recurrent()?.checked_mul(LstmLayer::GATES),
+            LayerKind::Gru => recurrent()?.checked_mul(GruLayer::GATES),
+
+            LayerKind::Plastic => neurons
+                .checked_mul(inputs.checked_mul(2)?.checked_add(bias)?)?
+                .checked_add(alpha),
+
+            LayerKind::Convolution(convolution) => {
+                ConvolutionLayer::checked_num_parameters(convolution, topology.bias)?
+                    .checked_add(alpha)
+            }
+
+            LayerKind::Custom => Self::custom_kind(),
+        }
+    }
+
+    fn custom_kind() -> ! {
+        panic!("custom layers can't be built from a topology, see `Network::insert_layer()`")
+    }
+
+    pub fn inputs(&self) -> usize {
+        match self {
+            Self::Dense(layer) => layer.inputs(),
+            Self::Recurrent(layer) => layer.inputs(),
+            Self::Lstm(layer) => layer.inputs(),
+            Self::Gru(layer) => layer.inputs(),
+            Self::Convolution(layer) => layer.inputs(),
+            Self::Plastic(layer) => layer.inputs(),
+            Self::Custom(layer) => layer.inputs(),
+        }
+    }
+
+    pub fn outputs(&self) -> usize {
+        match self {
+            Self::Dense(layer) => layer.outputs(),
+            Self::Recurrent(layer) => layer.outputs(),
+            Self::Lstm(layer) => layer.outputs(),
+            Self::Gru(layer) => layer.outputs(),
+            Self::Convolution(layer) => layer.outputs(),
+            Self::Plastic(layer) => layer.outputs(),
+            Self::Custom(layer) => layer.outputs(),
+        }
+    }
+
+    pub fn topology(&self) -> LayerTopology {
+        if let Self::Custom(layer) = self {
+            return LayerTopology {
+                neurons: layer.outputs(),
+                kind: LayerKind::Custom,
+                ..Default::default()
+            };
+        }
+
+        let (activation, kind) = match self {
+            Self::Dense(layer) => (layer.activation(), LayerKind::Dense),
+            Self::Recurrent(layer) => (layer.params().activation(), LayerKind::Recurrent),
+            Self::Lstm(_) => (Activation::default(), LayerKind::Lstm),
+            Self::Gru(_) => (Activation::default(), LayerKind::Gru),
+
+            Self::Convolution(layer) => (
+                layer.params().activation(),
+                LayerKind::Convolution(layer.convolution()),
+            ),
+
+            Self::Plastic(layer) => (layer.params().activation(), LayerKind::Plastic),
+
+            Self::Custom(_) => unreachable!(),
+        };
+
+        LayerTopology {
+            neurons: self.outputs(),
+            activation,
+            kind,
+            bias: self.params().has_bias(),
+            normalize: self.params().normalizes(),
+            mask: self.params().mask().map(<[bool]>::to_vec),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(test)]
+    pub fn propagate(&self, inputs: &[Float]) -> Vec<Float> {
+        let mut outputs = Vec::with_capacity(self.outputs());
+
+        self.propagate_into(&mut self.state(), inputs, &mut outputs);
+
+        outputs
+    }
+
+    /// Returns the state of this layer before it's ever been propagated.
+    pub fn state(&self) -> LayerState {
+        match self {
+            Self::Recurrent(layer) => LayerState::Recurrent(layer.state()),
+            Self::Lstm(layer) => LayerState::Lstm(layer.state()),
+            Self::Gru(layer) => LayerState::Gru(layer.state()),
+            Self::Plastic(layer) => LayerState::Plastic(layer.state()),
+            Self::Custom(layer) => LayerState::Custom(vec![0.0; layer.state_size()]),
+            _ => LayerState::None,
+        }
+    }
+
+    /// Overwrites `outputs` with this layer's outputs, reusing its allocation.
+    ///
+    /// Panics if `state` belongs to a different kind of layer.
+    pub fn propagate_into(
+        &self,
+        state: &mut LayerState,
+        inputs: &[Float],
+        outputs: &mut Vec<Float>,
+    ) {
+        match (self, state) {
+            (Self::Dense(layer), _) => layer.propagate_into(inputs, outputs),
+
+            (Self::Recurrent(layer), LayerState::Recurrent(state)) => {
+                layer.propagate_into(state, inputs, outputs)
+            }
+
+            (Self::Lstm(layer), LayerState::Lstm(state)) => {
+                layer.propagate_into(state, inputs, outputs)
+            }
+
+            (Self::Gru(layer), LayerState::Gru(state)) => {
+                layer.propagate_into(state, inputs, outputs)
+            }
+
+            (Self::Convolution(layer), _) => layer.propagate_into(inputs, outputs),
+            (Self::Plastic(layer), LayerState::Plastic(state)) => {
+                layer.propagate_into(state, inputs, outputs)
+            }
+
+            (Self::Custom(layer), LayerState::Custom(state)) => {
+                outputs.clear();
+                layer.propagate(state, inputs, outputs);
+
+                assert_eq!(
+                    outputs.len(),
+                    layer.outputs(),
+                    "custom layer `{}` returned wrong number of outputs",
+                    layer.name()
+                );
+            }
+
+            _ => panic!("state belongs to a different layer"),
+        }
+    }
+
+    /// Like [`Self::propagate_into()`], but adds `skip` to the weighted sums
+    /// before the activation; only dense layers support that.
+    pub fn propagate_skip_into(&self, inputs: &[Float], skip: &[Float], outputs: &mut Vec<Float>) {
+        match self {
+            Self::Dense(layer) => layer.propagate_skip_into(inputs, skip, outputs),
+            _ => unreachable!("only dense layers support skip connections"),
+        }
+    }
+
+    /// Returns the type of this layer, as shown e.g. by `Display`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Dense(_) => "dense",
+            Self::Recurrent(_) => "recurrent",
+            Self::Lstm(_) => "LSTM",
+            Self::Gru(_) => "GRU",
+            Self::Convolution(_) => "convolution",
+            Self::Plastic(_) => "plastic",
+            Self::Custom(layer) => layer.name(),
+        }
+    }
+
+    /// Returns whether this layer got implemented outside of this crate.
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom(_))
+    }
+
+    /// Returns the number of values yielded by [`Self::weights()`].
+    pub fn num_weights(&self) -> usize {
+        match self {
+            Self::Plastic(layer) => layer.num_parameters(),
+            Self::Custom(layer) => layer.num_parameters(),
+            _ => self.params().num_parameters(),
+        }
+    }
+
+    /// Returns this layer's parameters in the order they're laid out in the
+    /// network's genome.
+    pub fn weights(&self) -> Box<dyn Iterator<Item = Float> + '_> {
+        match self {
+            Self::Plastic(layer) => Box::new(layer.weights()),
+            Self::Custom(layer) => layer.weights(),
+            _ => Box::new(self.params().weights()),
+        }
+    }
+
+    pub fn weights_mut(&mut self) -> Box<dyn Iterator<Item = &mut Float> + '_> {
+        match self {
+            Self::Plastic(layer) => Box::new(layer.weights_mut()),
+            Self::Custom(layer) => layer.weights_mut(),
+            _ => Box::new(self.params_mut().weights_mut()),
+        }
+    }
+
+    /// Returns the matrix holding this layer's parameters; for stateful
+    /// layers its rows cover more than just the layer's inputs (see e.g.
+    /// [`RecurrentLayer`]), for convolutional ones each row is a filter.
+    ///
+    /// Panics for custom layers, whose parameters are opaque.
+    pub fn params(&self) -> &DenseLayer {
+        match self {
+            Self::Dense(layer) => layer,
+            Self::Recurrent(layer) => layer.params(),
+            Self::Lstm(layer) => layer.params(),
+            Self::Gru(layer) => layer.params(),
+            Self::Convolution(layer) => layer.params(),
+            Self::Plastic(layer) => layer.params(),
+            Self::Custom(layer) => panic!("custom layer `{}` has no matrix", layer.name()),
+        }
+    }
+
+    pub fn params_mut(&mut self) -> &mut DenseLayer {
+        match self {
+            Self::Dense(layer) => layer,
+            Self::Recurrent(layer) => layer.params_mut(),
+            Self::Lstm(layer) => layer.params_mut(),
+            Self::Gru(layer) => layer.params_mut(),
+            Self::Convolution(layer) => layer.params_mut(),
+            Self::Plastic(layer) => layer.params_mut(),
+            Self::Custom(layer) => panic!("custom layer `{}` has no matrix", layer.name()),
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use crate::*;
+
+/// Shape of a convolutional layer, which slides `filters` kernels over its
+/// inputs laid out as a `channels × height × width` image (channel by
+/// channel, each channel row by row), with a stride of 1 and no padding.
+///
+/// The image's height follows from the number of inputs, so for a 1D
+/// convolution (e.g. over a row of eye cells) set `width` to the number of
+/// inputs per channel and `kernel_height` to 1.
+///
+/// Outputs are laid out the same way, with one channel per filter, so
+/// convolutional layers can be stacked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Convolution {
+    pub channels: usize,
+    pub width: usize,
+    pub kernel_width: usize,
+    pub kernel_height: usize,
+    pub filters: usize,
+}
+
+impl Convolution {
+    /// Returns the number of outputs for given number of inputs, or `None`
+    /// if the inputs don't form an image the kernel fits into.
+    pub fn outputs(&self, inputs: usize) -> Option<usize> {
+        let (_, height, width) = self.shape(inputs)?;
+
+        self.filters.checked_mul(height)?.checked_mul(width)
+    }
+
+    /// Returns the number of weights of each filter.
+    fn kernel_size(&self) -> usize {
+        self.channels * self.kernel_height * self.kernel_width
+    }
+
+    /// Returns the input image's height and the output image's size.
+    fn shape(&self, inputs: usize) -> Option<(usize, usize, usize)> {
+        let row = self.channels.checked_mul(self.width)?;
+
+        if row == 0
+            || !inputs.is_multiple_of(row)
+            || self.kernel_height == 0
+            || self.kernel_width == 0
+        {
+            return None;
+        }
+
+        let height = inputs / row;
+        let output_height = (height + 1).checked_sub(self.kernel_height)?;
+        let output_width = (self.width + 1).checked_sub(self.kernel_width)?;
+
+        (output_height > 0 && output_width > 0).then_some((height, output_height, output_width))
+    }
+}
+
+/// Convolutional layer, see [`Convolution`].
+///
+/// Each filter is a row of `params`: its bias followed by its kernel, channel
+/// by channel, row by row.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConvolutionLayer {
+    convolution: Convolution,
+    height: usize,
+    params: DenseLayer,
+}
+
+impl ConvolutionLayer {
+    pub fn random(
+        rng: &mut dyn RngCore,
+        inputs: usize,
+        topology: &LayerTopology,
+        convolution: Convolution,
+    ) -> Self {
+        let params = DenseLayer::random(
+            rng,
+            convolution.kernel_size(),
+            &Self::params_topology(topology, convolution),
+        );
+
+        Self::new(inputs, topology, convolution, params)
+    }
+
+    pub fn from_weights(
+        inputs: usize,
+        topology: &LayerTopology,
+        convolution: Convolution,
+        weights: &mut dyn Iterator<Item = Float>,
+    ) -> Self {
+        let params = DenseLayer::from_weights(
+            convolution.kernel_size(),
+            &Self::params_topology(topology, convolution),
+            weights,
+        );
+
+        Self::new(inputs, topology, convolution, params)
+    }
+
+    /// Returns how many weights a layer of given shape has, or `None` if that
+    /// overflows.
+    pub(crate) fn checked_num_parameters(convolution: Convolution, bias: bool) -> Option<usize> {
+        let kernel_size = convolution
+            .channels
+            .checked_mul(convolution.kernel_height)?
+            .checked_mul(convolution.kernel_width)?;
+
+        convolution
+            .filters
+            .checked_mul(kernel_size.checked_add(usize::from(bias))?)
+    }
+
+    fn params_topology(topology: &LayerTopology, convolution: Convolution) -> LayerTopology {
+        LayerTopology {
+            neurons: convolution.filters,
+            activation: topology.activation,
+            kind: LayerKind::Dense,
+            init: topology.init.clone(),
+            bias: topology.bias,
+            normalize: topology.normalize,
+            ..Default::default()
+        }
+    }
+
+    fn new(
+        inputs: usize,
+        topology: &LayerTopology,
+        convolution: Convolution,
+        params: DenseLayer,
+    ) -> Self {
+        let (height, ..) = convolution
+            .shape(inputs)
+            .expect("inputs don't form an image the kernel fits into");
+
+        let layer = Self {
+            convolution,
+            height,
+            params,
+        };
+
+        assert_eq!(
+            layer.outputs(),
+            topology.neurons,
+            "number of neurons doesn't match the convolution's outputs"
+        );
+
+        layer
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.convolution.channels * self.height * self.convolution.width
+    }
+
+    pub fn outputs(&self) -> usize {
+        let (height, width) = self.output_shape();
+
+        self.convolution.filters * height * width
+    }
+
+    pub fn propagate_into(&self, inputs: &[Float], outputs: &mut Vec<Float>) {
+        assert_eq!(inputs.len(), self.inputs());
+
+        let Convolution {
+            channels,
+            width,
+            kernel_width,
+            kernel_height,
+            filters,
+        } = self.convolution;
+
+        let (output_height, output_width) = self.output_shape();
+
+        outputs.clear();
+
+        for filter in 0..filters {
+            let bias = self.params.bias(filter).unwrap();
+            let kernel = self.params.row(filter);
+
+            for y in 0..output_height {
+                for x in 0..output_width {
+                    let mut output = bias;
+
+                    for channel in 0..channels {
+                        for dy in 0..kernel_height {
+                            let row = (channel * self.height + y + dy) * width + x;
+                            let kernel_row = (channel * kernel_height + dy) * kernel_width;
+
+                            output += dot(
+                                &inputs[row..][..kernel_width],
+                                &kernel[kernel_row..][..kernel_width],
+                            );
+                        }
+                    }
+
+                    outputs.push(output);
+                }
+            }
+        }
+
+        self.params.activate(outputs);
+    }
+
+    pub fn convolution(&self) -> Convolution {
+        self.convolution
+    }
+
+    pub fn params(&self) -> &DenseLayer {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut DenseLayer {
+        &mut self.params
+    }
+
+    fn output_shape(&self) -> (usize, usize) {
+        (
+            self.height + 1 - self.convolution.kernel_height,
+            self.convolution.width + 1 - self.convolution.kernel_width,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn layer(inputs: usize, convolution: Convolution, weights: &[Float]) -> ConvolutionLayer {
+        let topology = LayerTopology {
+            neurons: convolution.outputs(inputs).unwrap(),
+            activation: Activation::Linear,
+            kind: LayerKind::Convolution(convolution),
+            ..Default::default()
+        };
+
+        ConvolutionLayer::from_weights(inputs, &topology, convolution, &mut weights.iter().copied())
+    }
+
+    mod outputs {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let convolution = Convolution {
+                channels: 2,
+                width: 4,
+                kernel_width: 3,
+                kernel_height: 2,
+                filters: 5,
+            };
+
+            // 2 channels × 3 rows × 4 columns -> 5 filters × 2 rows × 2 columns
+            assert_eq!(convolution.outputs(24), Some(20));
+
+            // Not a whole number of rows
+            assert_eq!(convolution.outputs(25), None);
+
+            // A single row, but the kernel has two
+            assert_eq!(convolution.outputs(8), None);
+        }
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn one_d() {
+            let convolution = Convolution {
+                channels: 1,
+                width: 4,
+                kernel_width: 2,
+                kernel_height: 1,
+                filters: 1,
+            };
+
+            let layer = layer(4, convolution, &[0.0, -1.0, 1.0]);
+            let mut outputs = Vec::new();
+
+            layer.propagate_into(&[1.0, 3.0, 6.0, 10.0], &mut outputs);
+
+            assert_relative_eq!(outputs.as_slice(), [2.0, 3.0, 4.0].as_ref());
+        }
+
+        #[test]
+        fn two_d() {
+            let convolution = Convolution {
+                channels: 1,
+                width: 3,
+                kernel_width: 2,
+                kernel_height: 2,
+                filters: 1,
+            };
+
+            let layer = layer(9, convolution, &[0.5, 1.0, 1.0, 1.0, 1.0]);
+            let mut outputs = Vec::new();
+
+            #[rustfmt::skip]
+            let inputs = [
+                1.0, 2.0, 3.0,
+                4.0, 5.0, 6.0,
+                7.0, 8.0, 9.0,
+            ];
+
+            layer.propagate_into(&inputs, &mut outputs);
+
+            assert_relative_eq!(outputs.as_slice(), [12.5, 16.5, 24.5, 28.5].as_ref());
+        }
+
+        #[test]
+        fn channels_and_filters() {
+            let convolution = Convolution {
+                channels: 2,
+                width: 2,
+                kernel_width: 1,
+                kernel_height: 1,
+                filters: 2,
+            };
+
+            // First filter picks the first channel, the second one picks the
+            // second channel and adds 1
+            let layer = layer(4, convolution, &[0.0, 1.0, 0.0, 1.0, 0.0, 1.0]);
+            let mut outputs = Vec::new();
+
+            layer.propagate_into(&[1.0, 2.0, 10.0, 20.0], &mut outputs);
+
+            assert_relative_eq!(outputs.as_slice(), [1.0, 2.0, 11.0, 21.0].as_ref());
+        }
+    }
+
+    mod random {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test() {
+            let convolution = Convolution {
+                channels: 2,
+                width: 4,
+                kernel_width: 3,
+                kernel_height: 2,
+                filters: 5,
+            };
+
+            let topology = LayerTopology {
+                neurons: 20,
+                kind: LayerKind::Convolution(convolution),
+                ..Default::default()
+            };
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layer = ConvolutionLayer::random(&mut rng, 24, &topology, convolution);
+
+            assert_eq!(layer.inputs(), 24);
+            assert_eq!(layer.outputs(), 20);
+
+            // Each filter: bias + 2 channels × 2 rows × 3 columns
+            assert_eq!(layer.params().weights().count(), 5 * (1 + 12));
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_wrong_neuron_count() {
+            let convolution = Convolution {
+                channels: 1,
+                width: 4,
+                kernel_width: 2,
+                kernel_height: 1,
+                filters: 1,
+            };
+
+            let topology = LayerTopology {
+                neurons: 4,
+                kind: LayerKind::Convolution(convolution),
+                ..Default::default()
+            };
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            ConvolutionLayer::random(&mut rng, 4, &topology, convolution);
+        }
+    }
+}
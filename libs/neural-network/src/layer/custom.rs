@@ -0,0 +1,154 @@
+use crate::*;
+use alloc::boxed::Box;
+use core::fmt;
+
+/// Layer implemented outside of this crate (e.g. a noisy layer or an
+/// attention block), see [`Network::insert_layer()`].
+///
+/// Custom layers can't be built out of a [`LayerTopology`], so they're
+/// reported as [`LayerKind::Custom`], which [`Network::random()`] and
+/// [`Network::from_weights()`] reject; to evolve a network containing them,
+/// update its parameters in place with [`Network::weights_mut()`] instead.
+///
+/// Features relying on the parameters forming a matrix (quantization,
+/// exports, training, pruning) don't support custom layers either.
+///
+/// Networks are shared between threads (e.g. to propagate the population
+/// in parallel), so custom layers need to be `Sync`; ones that keep memory
+/// get it handed over by [`Self::propagate()`], since it lives in the
+/// [`NetworkState`] rather than in the layer.
+pub trait CustomLayer: fmt::Debug + Send + Sync {
+    /// Returns the name of this layer's type, e.g. for `Display`.
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+
+    fn inputs(&self) -> usize;
+
+    fn outputs(&self) -> usize;
+
+    /// Returns how many values this layer remembers between calls to
+    /// [`Network::propagate_with()`], e.g. its previous outputs; they start
+    /// as zeros and get zeroed again by [`Network::reset_state()`].
+    fn state_size(&self) -> usize {
+        0
+    }
+
+    /// Appends this layer's outputs to `outputs`, which is empty; `state`
+    /// holds [`Self::state_size()`] values, which the layer may update for
+    /// the next call.
+    fn propagate(&self, state: &mut [Float], inputs: &[Float], outputs: &mut Vec<Float>);
+
+    /// Returns the number of values yielded by [`Self::weights()`].
+    fn num_parameters(&self) -> usize;
+
+    /// Returns this layer's parameters, always in the same order - that's
+    /// how they're laid out in the network's genome.
+    fn weights(&self) -> Box<dyn Iterator<Item = Float> + '_>;
+
+    /// Returns this layer's parameters in the same order as
+    /// [`Self::weights()`] does.
+    fn weights_mut(&mut self) -> Box<dyn Iterator<Item = &mut Float> + '_>;
+
+    /// Returns a layer of the same type and shape, with parameters taken from
+    /// `weights` in the order [`Self::weights()`] returns them.
+    fn with_weights(&self, weights: &mut dyn Iterator<Item = Float>) -> Box<dyn CustomLayer>;
+}
+
+/// Rebuilds the layer out of its own parameters, see
+/// [`CustomLayer::with_weights()`].
+impl Clone for Box<dyn CustomLayer> {
+    fn clone(&self) -> Self {
+        self.with_weights(&mut self.weights())
+    }
+}
+
+/// Multiplies each input by its own parameter, for testing custom layers.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct ScaleLayer(pub Vec<Float>);
+
+#[cfg(test)]
+impl CustomLayer for ScaleLayer {
+    fn name(&self) -> &'static str {
+        "scale"
+    }
+
+    fn inputs(&self) -> usize {
+        self.0.len()
+    }
+
+    fn outputs(&self) -> usize {
+        self.0.len()
+    }
+
+    fn propagate(&self, _: &mut [Float], inputs: &[Float], outputs: &mut Vec<Float>) {
+        outputs.extend(
+            inputs
+                .iter()
+                .zip(&self.0)
+                .map(|(input, scale)| input * scale),
+        );
+    }
+
+    fn num_parameters(&self) -> usize {
+        self.0.len()
+    }
+
+    fn weights(&self) -> Box<dyn Iterator<Item = Float> + '_> {
+        Box::new(self.0.iter().copied())
+    }
+
+    fn weights_mut(&mut self) -> Box<dyn Iterator<Item = &mut Float> + '_> {
+        Box::new(self.0.iter_mut())
+    }
+
+    fn with_weights(&self, weights: &mut dyn Iterator<Item = Float>) -> Box<dyn CustomLayer> {
+        Box::new(Self(weights.take(self.0.len()).collect()))
+    }
+}
+
+/// Outputs the sum of all inputs it's seen so far (each input separately),
+/// for testing stateful custom layers.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct SumLayer(pub usize);
+
+#[cfg(test)]
+impl CustomLayer for SumLayer {
+    fn inputs(&self) -> usize {
+        self.0
+    }
+
+    fn outputs(&self) -> usize {
+        self.0
+    }
+
+    fn state_size(&self) -> usize {
+        self.0
+    }
+
+    fn propagate(&self, state: &mut [Float], inputs: &[Float], outputs: &mut Vec<Float>) {
+        for (sum, input) in state.iter_mut().zip(inputs) {
+            *sum += input;
+        }
+
+        outputs.extend_from_slice(state);
+    }
+
+    fn num_parameters(&self) -> usize {
+        0
+    }
+
+    fn weights(&self) -> Box<dyn Iterator<Item = Float> + '_> {
+        Box::new(core::iter::empty())
+    }
+
+    fn weights_mut(&mut self) -> Box<dyn Iterator<Item = &mut Float> + '_> {
+        Box::new(core::iter::empty())
+    }
+
+    fn with_weights(&self, _: &mut dyn Iterator<Item = Float>) -> Box<dyn CustomLayer> {
+        Box::new(Self(self.0))
+    }
+}
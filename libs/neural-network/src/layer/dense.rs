@@ -0,0 +1,657 @@
+use crate::*;
+use core::{iter::once, ops::Range};
+
+/// Fully-connected layer, stored as a bias vector plus a row-major
+/// `outputs × inputs` weight matrix (row `n` holds the weights of the `n`-th
+/// neuron), so that propagating is a single matrix-vector multiplication.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DenseLayer {
+    inputs: usize,
+    biases: Vec<Float>,
+    weights: Vec<Float>,
+    activation: Activation,
+
+    /// Whether `biases` are parameters; if not, they're all zero
+    #[cfg_attr(feature = "serde", serde(default = "DenseLayer::default_bias"))]
+    bias: bool,
+
+    /// Slope for negative inputs if the activation is [`Activation::Prelu`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    alpha: Float,
+
+    /// See [`LayerTopology::normalize`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    normalize: bool,
+
+    /// See [`LayerTopology::mask`]; weights of missing connections are zero
+    #[cfg_attr(feature = "serde", serde(default))]
+    mask: Option<Vec<bool>>,
+}
+
+impl DenseLayer {
+    /// Number of neurons from which [`Self::extend_linear()`] goes parallel;
+    /// below that, spawning tasks costs more than it saves.
+    #[cfg(feature = "parallel")]
+    const PARALLEL_THRESHOLD: usize = 256;
+
+    pub fn new(biases: Vec<Float>, weights: Vec<Vec<Float>>, activation: Activation) -> Self {
+        assert_eq!(biases.len(), weights.len());
+
+        let inputs = weights.first().map_or(0, |row| row.len());
+
+        assert!(weights.iter().all(|row| row.len() == inputs));
+
+        Self {
+            inputs,
+            biases,
+            weights: weights.concat(),
+            activation,
+            bias: true,
+            alpha: Float::from(Activation::PRELU_ALPHA),
+            normalize: false,
+            mask: None,
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.biases.len()
+    }
+
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    /// Returns whether this layer's biases are parameters, see
+    /// [`LayerTopology::bias`].
+    pub fn has_bias(&self) -> bool {
+        self.bias
+    }
+
+    /// Returns whether this layer normalizes its weighted sums, see
+    /// [`LayerTopology::normalize`].
+    pub fn normalizes(&self) -> bool {
+        self.normalize
+    }
+
+    /// Returns which weights are parameters, see [`LayerTopology::mask`].
+    pub fn mask(&self) -> Option<&[bool]> {
+        self.mask.as_deref()
+    }
+
+    /// Returns whether given neuron is connected to its `index`-th input,
+    /// i.e. whether the weight between them isn't masked out.
+    pub fn is_connected(&self, neuron: usize, index: usize) -> bool {
+        self.mask
+            .as_ref()
+            .is_none_or(|mask| mask[neuron * self.inputs + index])
+    }
+
+    /// Zeroes weights of connections that are masked out, after updating
+    /// the whole matrix at once.
+    #[cfg(feature = "train")]
+    fn apply_mask(&mut self) {
+        if let Some(mask) = &self.mask {
+            for (weight, connected) in self.weights.iter_mut().zip(mask) {
+                if !connected {
+                    *weight = 0.0;
+                }
+            }
+        }
+    }
+
+    /// Returns the slope used by [`Activation::Prelu`].
+    pub fn prelu_alpha(&self) -> Float {
+        self.alpha
+    }
+
+    /// Returns the number of values yielded by [`Self::weights()`].
+    pub fn num_parameters(&self) -> usize {
+        let weights = match &self.mask {
+            Some(mask) => mask.iter().filter(|&&connected| connected).count(),
+            None => self.weights.len(),
+        };
+
+        self.num_biases() + weights + usize::from(self.activation == Activation::Prelu)
+    }
+
+    #[cfg(test)]
+    pub fn propagate(&self, inputs: &[Float]) -> Vec<Float> {
+        let mut outputs = Vec::with_capacity(self.outputs());
+
+        self.propagate_into(inputs, &mut outputs);
+
+        outputs
+    }
+
+    /// Overwrites `outputs` with this layer's outputs, reusing its allocation.
+    pub fn propagate_into(&self, inputs: &[Float], outputs: &mut Vec<Float>) {
+        assert_eq!(inputs.len(), self.inputs);
+
+        outputs.clear();
+
+        self.extend_linear(0..self.outputs(), inputs, outputs);
+        self.activate(outputs);
+    }
+
+    /// Like [`Self::propagate_into()`], but adds `skip` (e.g. outputs of an
+    /// earlier layer) to the weighted sums before the activation.
+    pub fn propagate_skip_into(&self, inputs: &[Float], skip: &[Float], outputs: &mut Vec<Float>) {
+        assert_eq!(skip.len(), self.outputs());
+
+        outputs.clear();
+
+        self.extend_linear(0..self.outputs(), inputs, outputs);
+
+        for (output, skip) in outputs.iter_mut().zip(skip) {
+            *output += skip;
+        }
+
+        self.activate(outputs);
+    }
+
+    /// Applies this layer's activation to `outputs`, normalizing them first
+    /// if the layer does so.
+    pub fn activate(&self, outputs: &mut [Float]) {
+        if self.normalize {
+            Self::normalize(outputs);
+        }
+
+        self.activation.apply_with(outputs, self.alpha);
+    }
+
+    /// Shifts and scales `values` to zero mean and unit variance.
+    pub fn normalize(values: &mut [Float]) {
+        let len = values.len().max(1) as Float;
+        let mean = values.iter().sum::<Float>() / len;
+        let variance = values
+            .iter()
+            .map(|x| (x - mean) * (x - mean))
+            .sum::<Float>()
+            / len;
+
+        // Keeps layers whose sums are all equal from dividing by zero
+        let scale = 1.0 / (variance + 1e-5).sqrt();
+
+        for value in values {
+            *value = (*value - mean) * scale;
+        }
+    }
+
+    /// Appends to `outputs` the given neurons' sums of their bias and
+    /// weighted inputs, without applying the activation - gated layers use
+    /// this to compute their gates separately.
+    ///
+    /// With the `parallel` feature, wide layers get split across threads.
+    pub fn extend_linear(&self, neurons: Range<usize>, inputs: &[Float], outputs: &mut Vec<Float>) {
+        assert_eq!(inputs.len(), self.inputs);
+
+        let linear = |neuron| self.biases[neuron] + dot(inputs, self.row(neuron));
+
+        #[cfg(feature = "parallel")]
+        if neurons.len() >= Self::PARALLEL_THRESHOLD {
+            use rayon::prelude::*;
+
+            outputs.par_extend(neurons.into_par_iter().map(linear));
+            return;
+        }
+
+        outputs.extend(neurons.map(linear));
+    }
+
+    /// Performs a step of gradient descent, given `inputs` of the latest
+    /// propagation and the loss' gradients with respect to the weighted sums
+    /// (i.e. already passed back through the activation); returns gradients
+    /// with respect to the inputs.
+    #[cfg(feature = "train")]
+    pub fn backpropagate(
+        &mut self,
+        inputs: &[Float],
+        grads: Vec<Float>,
+        learning_rate: Float,
+    ) -> Vec<Float> {
+        let mut input_grads = vec![0.0; self.inputs];
+
+        for (neuron, grad) in grads.iter().enumerate() {
+            for (input_grad, weight) in input_grads.iter_mut().zip(self.row(neuron)) {
+                *input_grad += grad * weight;
+            }
+        }
+
+        for (neuron, grad) in grads.iter().enumerate() {
+            if self.bias {
+                self.biases[neuron] -= learning_rate * grad;
+            }
+
+            for (weight, input) in self.weights[neuron * self.inputs..][..self.inputs]
+                .iter_mut()
+                .zip(inputs)
+            {
+                *weight -= learning_rate * grad * input;
+            }
+        }
+
+        self.apply_mask();
+
+        input_grads
+    }
+
+    pub fn random(rng: &mut dyn RngCore, inputs: usize, topology: &LayerTopology) -> Self {
+        let sampler = topology.init.sampler(rng, inputs, topology.neurons);
+        let mut layer = Self::generate(inputs, topology, sampler);
+
+        // PReLU's alpha isn't a weight, so it starts at the usual value
+        // rather than being drawn from the init distribution
+        layer.alpha = Float::from(Activation::PRELU_ALPHA);
+        layer
+    }
+
+    pub fn from_weights(
+        inputs: usize,
+        topology: &LayerTopology,
+        weights: &mut dyn Iterator<Item = Float>,
+    ) -> Self {
+        Self::generate(inputs, topology, || {
+            weights.next().expect("got not enough weights")
+        })
+    }
+
+    /// Returns this layer's parameters neuron by neuron - each neuron's bias
+    /// (unless the layer has none) followed by its weights (except for the
+    /// masked out ones) - and then the alpha of [`Activation::Prelu`] layers.
+    pub fn weights(&self) -> impl Iterator<Item = Float> + '_ {
+        self.biases
+            .iter()
+            .zip(self.rows())
+            .enumerate()
+            .flat_map(move |(neuron, (bias, row))| {
+                let row = row
+                    .iter()
+                    .enumerate()
+                    .filter(move |&(index, _)| self.is_connected(neuron, index))
+                    .map(|(_, weight)| weight);
+
+                once(bias).filter(|_| self.bias).chain(row)
+            })
+            .chain(once(&self.alpha).filter(|_| self.activation == Activation::Prelu))
+            .copied()
+    }
+
+    /// Returns this layer's parameters in the same order as
+    /// [`Self::weights()`] does.
+    pub fn weights_mut(&mut self) -> impl Iterator<Item = &mut Float> + '_ {
+        let (inputs, has_bias, mask) = (self.inputs, self.bias, self.mask.as_deref());
+        let mut weights = self.weights.as_mut_slice();
+
+        let rows = core::iter::repeat_with(move || {
+            let (row, rest) = core::mem::take(&mut weights).split_at_mut(inputs);
+
+            weights = rest;
+            row
+        });
+
+        let alpha = (self.activation == Activation::Prelu).then_some(&mut self.alpha);
+
+        self.biases
+            .iter_mut()
+            .zip(rows)
+            .enumerate()
+            .flat_map(move |(neuron, (bias, row))| {
+                let row = row
+                    .iter_mut()
+                    .enumerate()
+                    .filter(move |&(index, _)| {
+                        mask.is_none_or(|mask| mask[neuron * inputs + index])
+                    })
+                    .map(|(_, weight)| weight);
+
+                has_bias.then_some(bias).into_iter().chain(row)
+            })
+            .chain(alpha)
+    }
+
+    /// Appends a neuron, drawing its bias (if the layer has biases) and then
+    /// its weights from `next`.
+    pub fn push_neuron(&mut self, mut next: impl FnMut() -> Float) {
+        self.biases.push(if self.bias { next() } else { 0.0 });
+        self.weights.extend((0..self.inputs).map(|_| next()));
+    }
+
+    /// Removes given neuron, along with its bias and weights.
+    pub fn remove_neuron(&mut self, neuron: usize) {
+        self.biases.remove(neuron);
+        self.weights
+            .drain(neuron * self.inputs..(neuron + 1) * self.inputs);
+    }
+
+    /// Appends an input, which each neuron weights with zero.
+    pub fn push_input(&mut self) {
+        let weights = self
+            .rows()
+            .flat_map(|row| row.iter().copied().chain(once(0.0)))
+            .collect();
+
+        self.weights = weights;
+        self.inputs += 1;
+    }
+
+    /// Removes given input, along with each neuron's weight for it.
+    pub fn remove_input(&mut self, input: usize) {
+        let inputs = self.inputs;
+        let mut index = 0;
+
+        self.weights.retain(|_| {
+            index += 1;
+            (index - 1) % inputs != input
+        });
+
+        self.inputs -= 1;
+    }
+
+    /// Returns weights of all neurons, row by row, without the biases.
+    pub fn weight_matrix_mut(&mut self) -> &mut [Float] {
+        &mut self.weights
+    }
+
+    /// Returns the bias of given neuron, which is zero if the layer has no
+    /// biases.
+    pub fn bias(&self, neuron: usize) -> Option<Float> {
+        self.biases.get(neuron).copied()
+    }
+
+    /// Returns the bias of given neuron, unless the layer has no biases.
+    pub fn bias_mut(&mut self, neuron: usize) -> Option<&mut Float> {
+        self.biases.get_mut(neuron).filter(|_| self.bias)
+    }
+
+    pub fn weight(&self, neuron: usize, index: usize) -> Option<Float> {
+        self.weight_index(neuron, index)
+            .map(|index| self.weights[index])
+    }
+
+    pub fn weight_mut(&mut self, neuron: usize, index: usize) -> Option<&mut Float> {
+        self.weight_index(neuron, index)
+            .map(|index| &mut self.weights[index])
+    }
+
+    pub fn row(&self, neuron: usize) -> &[Float] {
+        &self.weights[neuron * self.inputs..][..self.inputs]
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Float]> {
+        (0..self.outputs()).map(|neuron| self.row(neuron))
+    }
+
+    fn num_biases(&self) -> usize {
+        if self.bias {
+            self.biases.len()
+        } else {
+            0
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn default_bias() -> bool {
+        true
+    }
+
+    fn weight_index(&self, neuron: usize, index: usize) -> Option<usize> {
+        (neuron < self.outputs() && index < self.inputs).then_some(neuron * self.inputs + index)
+    }
+
+    /// Builds a layer drawing its parameters from `next` in the same order as
+    /// [`Self::weights()`] returns them.
+    fn generate(inputs: usize, topology: &LayerTopology, mut next: impl FnMut() -> Float) -> Self {
+        let mut biases = Vec::with_capacity(topology.neurons);
+        let mut weights = Vec::with_capacity(topology.neurons * inputs);
+
+        if let Some(mask) = &topology.mask {
+            assert_eq!(
+                mask.len(),
+                topology.neurons * inputs,
+                "mask doesn't match the layer's weights"
+            );
+        }
+
+        let connected = |index| topology.mask.as_ref().is_none_or(|mask| mask[index]);
+
+        for neuron in 0..topology.neurons {
+            biases.push(if topology.bias { next() } else { 0.0 });
+
+            weights.extend((0..inputs).map(|index| {
+                if connected(neuron * inputs + index) {
+                    next()
+                } else {
+                    0.0
+                }
+            }));
+        }
+
+        let alpha = if topology.activation == Activation::Prelu {
+            next()
+        } else {
+            Float::from(Activation::PRELU_ALPHA)
+        };
+
+        Self {
+            inputs,
+            biases,
+            weights,
+            activation: topology.activation,
+            bias: topology.bias,
+            alpha,
+            normalize: topology.normalize,
+            mask: topology.mask.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    mod random {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        fn topology(neurons: usize) -> LayerTopology {
+            LayerTopology {
+                neurons,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layer = DenseLayer::random(&mut rng, 3, &topology(2));
+
+            let actual: Vec<_> = layer.rows().collect();
+
+            let expected: Vec<&[Float]> = vec![
+                &[0.67383957, 0.8181262, 0.26284897],
+                &[-0.53516835, 0.069369674, -0.7648182],
+            ];
+
+            approx::assert_relative_eq!(
+                actual.as_slice(),
+                expected.as_slice(),
+                max_relative = TOLERANCE
+            );
+        }
+
+        #[test]
+        fn draws_bias_before_weights() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layer = DenseLayer::random(&mut rng, 4, &topology(1));
+
+            assert_relative_eq!(
+                layer.biases.as_slice(),
+                [-0.6255188].as_ref(),
+                max_relative = TOLERANCE
+            );
+
+            assert_relative_eq!(
+                layer.row(0),
+                [0.67383957, 0.8181262, 0.26284897, 0.5238807,].as_ref(),
+                max_relative = TOLERANCE
+            );
+        }
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layer = DenseLayer::new(vec![0.5], vec![vec![-0.3, 0.8]], Activation::Linear);
+
+            assert_relative_eq!(layer.propagate(&[-10.0, -10.0])[0], -4.5,);
+
+            approx::assert_relative_eq!(
+                layer.propagate(&[0.5, 1.0])[0],
+                (-0.3 * 0.5) + (0.8 * 1.0) + 0.5,
+            );
+        }
+
+        #[test]
+        fn normalize() {
+            let mut values = [1.0, 2.0, 3.0, 6.0];
+
+            DenseLayer::normalize(&mut values);
+
+            let mean = values.iter().sum::<Float>() / 4.0;
+            let variance = values.iter().map(|x| x * x).sum::<Float>() / 4.0;
+
+            assert_relative_eq!(mean, 0.0, epsilon = 1e-6);
+            assert_relative_eq!(variance, 1.0, epsilon = 1e-4);
+            assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        #[test]
+        fn wide_layer() {
+            // Wide enough to go parallel with the `parallel` feature
+            let biases: Vec<_> = (0..1000).map(|neuron| neuron as Float).collect();
+            let weights = vec![vec![1.0, -2.0]; 1000];
+            let layer = DenseLayer::new(biases, weights, Activation::Linear);
+
+            let actual = layer.propagate(&[0.5, 0.25]);
+            let expected: Vec<_> = (0..1000).map(|neuron| neuron as Float).collect();
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn applies_activation() {
+            let layer = DenseLayer::new(
+                vec![0.1, 0.5],
+                vec![vec![0.2, 0.3, 0.4], vec![0.6, 0.7, 0.8]],
+                Activation::Sigmoid,
+            );
+
+            let input = vec![-0.5, 0.0, 0.5];
+            let actual = layer.propagate(&input);
+
+            let mut expected = vec![
+                0.1 + dot(&input, &[0.2, 0.3, 0.4]),
+                0.5 + dot(&input, &[0.6, 0.7, 0.8]),
+            ];
+
+            Activation::Sigmoid.apply(&mut expected);
+
+            assert_relative_eq!(actual.as_slice(), expected.as_slice());
+        }
+
+        #[test]
+        fn relu_clamps_negative_outputs() {
+            let layer = DenseLayer::new(vec![0.5], vec![vec![-0.3, 0.8]], Activation::Relu);
+            let actual = layer.propagate(&[-10.0, -10.0]);
+
+            assert_relative_eq!(actual.as_slice(), [0.0].as_ref());
+        }
+
+        #[test]
+        fn softmax_yields_distribution() {
+            let layer = DenseLayer::new(
+                vec![1.0, 0.0, -1.0],
+                vec![vec![1.0], vec![1.0], vec![1.0]],
+                Activation::Softmax,
+            );
+
+            let actual = layer.propagate(&[2.0]);
+
+            assert_relative_eq!(actual.iter().sum::<Float>(), 1.0, max_relative = TOLERANCE);
+
+            assert_relative_eq!(
+                actual.as_slice(),
+                [0.66524094, 0.24472848, 0.09003057].as_ref(),
+                max_relative = TOLERANCE
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_wrong_input_size() {
+            DenseLayer::new(vec![0.5], vec![vec![-0.3, 0.8]], Activation::Relu).propagate(&[1.0]);
+        }
+    }
+
+    mod extend_linear {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layer = DenseLayer::new(
+                vec![0.1, 0.5, -0.5],
+                vec![vec![0.2, 0.3], vec![0.6, 0.7], vec![-1.0, -2.0]],
+                Activation::Relu,
+            );
+
+            let mut outputs = vec![1.0];
+
+            layer.extend_linear(1..3, &[1.0, 1.0], &mut outputs);
+
+            assert_relative_eq!(outputs.as_slice(), [1.0, 1.8, -3.5].as_ref());
+        }
+    }
+
+    mod weights {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layer = DenseLayer::new(
+                vec![0.1, 0.5],
+                vec![vec![0.2, 0.3], vec![0.6, 0.7]],
+                Activation::Relu,
+            );
+
+            let actual: Vec<_> = layer.weights().collect();
+
+            assert_relative_eq!(actual.as_slice(), [0.1, 0.2, 0.3, 0.5, 0.6, 0.7].as_ref());
+        }
+    }
+
+    mod weight {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layer = DenseLayer::new(
+                vec![0.1, 0.5],
+                vec![vec![0.2, 0.3], vec![0.6, 0.7]],
+                Activation::Relu,
+            );
+
+            assert_eq!(layer.weight(1, 0), Some(0.6));
+            assert_eq!(layer.weight(0, 2), None);
+            assert_eq!(layer.weight(2, 0), None);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use crate::*;
+
+/// Gated recurrent unit layer - a lighter alternative to [`LstmLayer`],
+/// whose neurons interpolate between their previous output and a new
+/// candidate.
+///
+/// Parameters are stored as one matrix over the current inputs followed by
+/// the previous outputs, holding `3 × outputs` rows grouped by gate: first
+/// all update gates, then all reset gates and finally all candidates (whose
+/// recurrent weights get multiplied by the previous outputs already scaled by
+/// the reset gates). Gates use sigmoid and candidates use tanh, so the
+/// topology's activation is ignored.
+///
+/// The memory lives in a [`GruState`] owned by the caller.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GruLayer {
+    params: DenseLayer,
+}
+
+/// Memory of a [`GruLayer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GruState {
+    /// Current inputs followed by the previous outputs
+    context: Vec<Float>,
+
+    /// Current inputs followed by the previous outputs scaled by the reset
+    /// gates, i.e. what the candidates get multiplied by
+    reset_context: Vec<Float>,
+
+    /// Scratch space for the gates, so that propagating doesn't allocate
+    gates: Vec<Float>,
+}
+
+impl GruLayer {
+    pub const GATES: usize = 3;
+
+    pub fn random(rng: &mut dyn RngCore, inputs: usize, topology: &LayerTopology) -> Self {
+        Self::new(DenseLayer::random(
+            rng,
+            inputs + topology.neurons,
+            &Self::params_topology(topology),
+        ))
+    }
+
+    pub fn from_weights(
+        inputs: usize,
+        topology: &LayerTopology,
+        weights: &mut dyn Iterator<Item = Float>,
+    ) -> Self {
+        Self::new(DenseLayer::from_weights(
+            inputs + topology.neurons,
+            &Self::params_topology(topology),
+            weights,
+        ))
+    }
+
+    fn params_topology(topology: &LayerTopology) -> LayerTopology {
+        LayerTopology {
+            neurons: Self::GATES * topology.neurons,
+            activation: Activation::Linear,
+            kind: LayerKind::Dense,
+            init: topology.init.clone(),
+            bias: topology.bias,
+            ..Default::default()
+        }
+    }
+
+    fn new(params: DenseLayer) -> Self {
+        Self { params }
+    }
+
+    /// Returns the state of a layer that has never been propagated.
+    pub fn state(&self) -> GruState {
+        GruState {
+            context: vec![0.0; self.params.inputs()],
+            reset_context: vec![0.0; self.params.inputs()],
+            gates: Vec::with_capacity(self.params.outputs()),
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.params.inputs() - self.outputs()
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.params.outputs() / Self::GATES
+    }
+
+    pub fn propagate_into(&self, state: &mut GruState, inputs: &[Float], outputs: &mut Vec<Float>) {
+        assert_eq!(inputs.len(), self.inputs());
+
+        let GruState {
+            context,
+            reset_context,
+            gates,
+        } = state;
+
+        let neurons = self.outputs();
+
+        context[..inputs.len()].copy_from_slice(inputs);
+        gates.clear();
+        self.params.extend_linear(0..2 * neurons, context, gates);
+        Activation::Sigmoid.apply(gates);
+
+        let (context_inputs, previous) = context.split_at(inputs.len());
+
+        reset_context[..inputs.len()].copy_from_slice(context_inputs);
+
+        for ((reset, previous), reset_gate) in reset_context[inputs.len()..]
+            .iter_mut()
+            .zip(previous)
+            .zip(&gates[neurons..])
+        {
+            *reset = reset_gate * previous;
+        }
+
+        self.params
+            .extend_linear(2 * neurons..3 * neurons, reset_context, gates);
+
+        Activation::Tanh.apply(&mut gates[2 * neurons..]);
+
+        outputs.clear();
+
+        outputs.extend((0..neurons).map(|neuron| {
+            let update_gate = gates[neuron];
+            let candidate = gates[2 * neurons + neuron];
+
+            (1.0 - update_gate) * candidate + update_gate * previous[neuron]
+        }));
+
+        context[inputs.len()..].copy_from_slice(outputs);
+    }
+
+    pub fn params(&self) -> &DenseLayer {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut DenseLayer {
+        &mut self.params
+    }
+}
+
+impl GruState {
+    /// Forgets the previous outputs, as if the layer has never been
+    /// propagated.
+    pub fn reset(&mut self) {
+        self.context.fill(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn topology(neurons: usize) -> LayerTopology {
+        LayerTopology {
+            neurons,
+            kind: LayerKind::Gru,
+            ..Default::default()
+        }
+    }
+
+    /// Single neuron with both gates half-open and a candidate of
+    /// `tanh(input + reset * previous)`
+    fn layer() -> GruLayer {
+        #[rustfmt::skip]
+        let weights = [
+            0.0, 0.0, 0.0, // update gate
+            0.0, 0.0, 0.0, // reset gate
+            0.0, 1.0, 1.0, // candidate
+        ];
+
+        GruLayer::from_weights(1, &topology(1), &mut weights.into_iter())
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn remembers_previous_outputs() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            for (inputs, expected) in [(1.0, 0.3807971), (1.0, 0.6057498), (0.0, 0.44984552)] {
+                layer.propagate_into(&mut state, &[inputs], &mut outputs);
+
+                assert_relative_eq!(outputs[0], expected, max_relative = TOLERANCE);
+            }
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_wrong_input_size() {
+            let layer = layer();
+
+            layer.propagate_into(&mut layer.state(), &[1.0, 2.0], &mut Vec::new());
+        }
+    }
+
+    mod reset {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            state.reset();
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+
+            assert_relative_eq!(outputs[0], 0.3807971, max_relative = TOLERANCE);
+        }
+    }
+
+    mod random {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layer = GruLayer::random(&mut rng, 2, &topology(3));
+
+            assert_eq!(layer.inputs(), 2);
+            assert_eq!(layer.outputs(), 3);
+
+            // Each of the 3 gates of each neuron: bias + 2 input weights + 3
+            // recurrent weights
+            assert_eq!(layer.params().weights().count(), 3 * 3 * (1 + 2 + 3));
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use crate::*;
+
+/// Long short-term memory layer - a recurrent layer whose neurons (cells)
+/// decide through gates what to remember, what to forget and what to output,
+/// which lets them keep information around for much longer than
+/// [`RecurrentLayer`] does.
+///
+/// Parameters are stored as one matrix over the current inputs followed by
+/// the previous outputs, holding `4 × outputs` rows grouped by gate: first
+/// all input gates, then all forget gates, then all output gates and finally
+/// all candidates. Gates use sigmoid and candidates use tanh, so the
+/// topology's activation is ignored.
+///
+/// The memory lives in an [`LstmState`] owned by the caller.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LstmLayer {
+    params: DenseLayer,
+}
+
+/// Memory of an [`LstmLayer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LstmState {
+    /// Current inputs followed by the previous outputs
+    context: Vec<Float>,
+
+    /// Cells' values from the previous call
+    cells: Vec<Float>,
+
+    /// Scratch space for the gates, so that propagating doesn't allocate
+    gates: Vec<Float>,
+}
+
+impl LstmLayer {
+    pub const GATES: usize = 4;
+
+    pub fn random(rng: &mut dyn RngCore, inputs: usize, topology: &LayerTopology) -> Self {
+        Self::new(DenseLayer::random(
+            rng,
+            inputs + topology.neurons,
+            &Self::params_topology(topology),
+        ))
+    }
+
+    pub fn from_weights(
+        inputs: usize,
+        topology: &LayerTopology,
+        weights: &mut dyn Iterator<Item = Float>,
+    ) -> Self {
+        Self::new(DenseLayer::from_weights(
+            inputs + topology.neurons,
+            &Self::params_topology(topology),
+            weights,
+        ))
+    }
+
+    fn params_topology(topology: &LayerTopology) -> LayerTopology {
+        LayerTopology {
+            neurons: Self::GATES * topology.neurons,
+            activation: Activation::Linear,
+            kind: LayerKind::Dense,
+            init: topology.init.clone(),
+            bias: topology.bias,
+            ..Default::default()
+        }
+    }
+
+    fn new(params: DenseLayer) -> Self {
+        Self { params }
+    }
+
+    /// Returns the state of a layer that has never been propagated.
+    pub fn state(&self) -> LstmState {
+        LstmState {
+            context: vec![0.0; self.params.inputs()],
+            cells: vec![0.0; self.outputs()],
+            gates: Vec::with_capacity(self.params.outputs()),
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.params.inputs() - self.outputs()
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.params.outputs() / Self::GATES
+    }
+
+    pub fn propagate_into(
+        &self,
+        state: &mut LstmState,
+        inputs: &[Float],
+        outputs: &mut Vec<Float>,
+    ) {
+        assert_eq!(inputs.len(), self.inputs());
+
+        let LstmState {
+            context,
+            cells,
+            gates,
+        } = state;
+
+        let neurons = self.outputs();
+
+        context[..inputs.len()].copy_from_slice(inputs);
+        gates.clear();
+        self.params
+            .extend_linear(0..self.params.outputs(), context, gates);
+
+        let (sigmoids, candidates) = gates.split_at_mut(3 * neurons);
+
+        Activation::Sigmoid.apply(sigmoids);
+        Activation::Tanh.apply(candidates);
+
+        let (input_gates, rest) = sigmoids.split_at(neurons);
+        let (forget_gates, output_gates) = rest.split_at(neurons);
+
+        outputs.clear();
+
+        for neuron in 0..neurons {
+            let cell = &mut cells[neuron];
+
+            *cell = forget_gates[neuron] * *cell + input_gates[neuron] * candidates[neuron];
+            outputs.push(output_gates[neuron] * cell.tanh());
+        }
+
+        context[inputs.len()..].copy_from_slice(outputs);
+    }
+
+    pub fn params(&self) -> &DenseLayer {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut DenseLayer {
+        &mut self.params
+    }
+}
+
+impl LstmState {
+    /// Forgets the previous outputs and cells' values, as if the layer has
+    /// never been propagated.
+    pub fn reset(&mut self) {
+        self.context.fill(0.0);
+        self.cells.fill(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn topology(neurons: usize) -> LayerTopology {
+        LayerTopology {
+            neurons,
+            kind: LayerKind::Lstm,
+            ..Default::default()
+        }
+    }
+
+    /// Single cell with all gates half-open and a candidate of `tanh(input)`
+    fn layer() -> LstmLayer {
+        #[rustfmt::skip]
+        let weights = [
+            0.0, 0.0, 0.0, // input gate
+            0.0, 0.0, 0.0, // forget gate
+            0.0, 0.0, 0.0, // output gate
+            0.0, 1.0, 0.0, // candidate
+        ];
+
+        LstmLayer::from_weights(1, &topology(1), &mut weights.into_iter())
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn remembers_cells() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            for expected in [0.18169974, 0.2581184] {
+                layer.propagate_into(&mut state, &[1.0], &mut outputs);
+
+                assert_relative_eq!(outputs[0], expected, max_relative = TOLERANCE);
+            }
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_wrong_input_size() {
+            let layer = layer();
+
+            layer.propagate_into(&mut layer.state(), &[1.0, 2.0], &mut Vec::new());
+        }
+    }
+
+    mod reset {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            state.reset();
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+
+            assert_relative_eq!(outputs[0], 0.18169974, max_relative = TOLERANCE);
+        }
+    }
+
+    mod random {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layer = LstmLayer::random(&mut rng, 2, &topology(3));
+
+            assert_eq!(layer.inputs(), 2);
+            assert_eq!(layer.outputs(), 3);
+
+            // Each of the 4 gates of each cell: bias + 2 input weights + 3
+            // recurrent weights
+            assert_eq!(layer.params().weights().count(), 4 * 3 * (1 + 2 + 3));
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::*;
+
+/// Fully-connected layer whose weights adapt while it's being propagated,
+/// following Hebb's rule - connections between neurons that fire together
+/// get stronger.
+///
+/// Each connection has two parameters: its weight and its learning rate.
+/// Propagating uses the weight plus a Hebbian trace, which then grows by
+/// `rate * input * output` (clamped to `[-1, 1]`, so that co-activation
+/// can't blow the weights up); negative rates make the connection
+/// anti-Hebbian. Evolution tunes the rates, while the traces are learned
+/// within a lifetime and live in a [`PlasticState`] owned by the caller, same
+/// as the state of [`RecurrentLayer`], until [`Network::reset_state()`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlasticLayer {
+    params: DenseLayer,
+
+    /// Learning rate of each connection, laid out like the weight matrix
+    rates: Vec<Float>,
+}
+
+/// Memory of a [`PlasticLayer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlasticState {
+    /// Hebbian trace of each connection, laid out like the weight matrix
+    traces: Vec<Float>,
+}
+
+impl PlasticLayer {
+    pub fn random(rng: &mut dyn RngCore, inputs: usize, topology: &LayerTopology) -> Self {
+        let params = DenseLayer::random(rng, inputs, topology);
+        let mut sampler = topology.init.sampler(rng, inputs, topology.neurons);
+        let rates = (0..params.inputs() * params.outputs())
+            .map(|_| sampler())
+            .collect();
+
+        Self::new(params, rates)
+    }
+
+    pub fn from_weights(
+        inputs: usize,
+        topology: &LayerTopology,
+        weights: &mut dyn Iterator<Item = Float>,
+    ) -> Self {
+        let params = DenseLayer::from_weights(inputs, topology, weights);
+
+        let rates = (0..params.inputs() * params.outputs())
+            .map(|_| weights.next().expect("got not enough weights"))
+            .collect();
+
+        Self::new(params, rates)
+    }
+
+    fn new(params: DenseLayer, rates: Vec<Float>) -> Self {
+        Self { params, rates }
+    }
+
+    /// Returns the state of a layer that has never been propagated.
+    pub fn state(&self) -> PlasticState {
+        PlasticState {
+            traces: vec![0.0; self.rates.len()],
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.params.inputs()
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.params.outputs()
+    }
+
+    pub fn propagate_into(
+        &self,
+        state: &mut PlasticState,
+        inputs: &[Float],
+        outputs: &mut Vec<Float>,
+    ) {
+        assert_eq!(inputs.len(), self.inputs());
+
+        let traces = &mut state.traces;
+
+        outputs.clear();
+        self.params
+            .extend_linear(0..self.outputs(), inputs, outputs);
+
+        for (output, traces) in outputs.iter_mut().zip(traces.chunks(inputs.len())) {
+            *output += dot(inputs, traces);
+        }
+
+        self.params.activate(outputs);
+
+        let rows = traces
+            .chunks_mut(inputs.len())
+            .zip(self.rates.chunks(inputs.len()));
+
+        for (output, (traces, rates)) in outputs.iter().zip(rows) {
+            for ((trace, rate), input) in traces.iter_mut().zip(rates).zip(inputs) {
+                *trace = (*trace + rate * input * output).clamp(-1.0, 1.0);
+            }
+        }
+    }
+
+    /// Returns the layer's weights and biases, without the Hebbian traces.
+    pub fn params(&self) -> &DenseLayer {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut DenseLayer {
+        &mut self.params
+    }
+
+    /// Returns the learning rate of each connection, row by row like
+    /// [`DenseLayer::row()`].
+    #[cfg(feature = "npz")]
+    pub fn rates(&self) -> &[Float] {
+        &self.rates
+    }
+
+    #[cfg(feature = "npz")]
+    pub fn rates_mut(&mut self) -> &mut [Float] {
+        &mut self.rates
+    }
+
+    /// Returns the number of values yielded by [`Self::weights()`].
+    pub fn num_parameters(&self) -> usize {
+        self.params.num_parameters() + self.rates.len()
+    }
+
+    /// Returns parameters of [`Self::params()`], followed by the learning
+    /// rates.
+    pub fn weights(&self) -> impl Iterator<Item = Float> + '_ {
+        self.params.weights().chain(self.rates.iter().copied())
+    }
+
+    pub fn weights_mut(&mut self) -> impl Iterator<Item = &mut Float> + '_ {
+        self.params.weights_mut().chain(&mut self.rates)
+    }
+}
+
+impl PlasticState {
+    /// Forgets everything learned since the layer got created.
+    pub fn reset(&mut self) {
+        self.traces.fill(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn topology(neurons: usize) -> LayerTopology {
+        LayerTopology {
+            neurons,
+            activation: Activation::Linear,
+            kind: LayerKind::Plastic,
+            ..Default::default()
+        }
+    }
+
+    /// Single neuron with bias 0.0, weight 0.5 and learning rate 0.25
+    fn layer() -> PlasticLayer {
+        PlasticLayer::from_weights(1, &topology(1), &mut [0.0, 0.5, 0.25].into_iter())
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn strengthens_coactive_connections() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            // Trace grows by 0.25 * input * output after each call
+            for expected in [0.5, 0.625, 0.78125] {
+                layer.propagate_into(&mut state, &[1.0], &mut outputs);
+
+                assert_relative_eq!(outputs[0], expected);
+            }
+        }
+
+        #[test]
+        fn clamps_traces() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            for _ in 0..100 {
+                layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            }
+
+            assert_relative_eq!(outputs[0], 1.5);
+        }
+
+        #[test]
+        fn keeps_parameters() {
+            let layer = layer();
+
+            layer.propagate_into(&mut layer.state(), &[1.0], &mut Vec::new());
+
+            assert_eq!(layer.weights().collect::<Vec<_>>(), [0.0, 0.5, 0.25]);
+        }
+    }
+
+    mod reset {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            state.reset();
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+
+            assert_relative_eq!(outputs.as_slice(), [0.5].as_ref());
+        }
+    }
+
+    mod random {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layer = PlasticLayer::random(&mut rng, 3, &topology(2));
+
+            assert_eq!(layer.inputs(), 3);
+            assert_eq!(layer.outputs(), 2);
+            assert_eq!(layer.num_parameters(), 2 * (1 + 3) + 6);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::*;
+
+/// Fully-connected layer which also sees its own outputs from the previous
+/// call (i.e. an Elman layer), giving the network a short-term memory.
+///
+/// Each neuron has `inputs + outputs` weights: first for the current inputs,
+/// then for the previous outputs. The previous outputs live in a
+/// [`RecurrentState`] owned by the caller, so that the layer itself stays
+/// immutable while being propagated.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecurrentLayer {
+    params: DenseLayer,
+}
+
+/// Memory of a [`RecurrentLayer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecurrentState {
+    /// Current inputs followed by the previous outputs, i.e. what the layer's
+    /// parameters get multiplied by; kept around so that propagating doesn't
+    /// allocate.
+    context: Vec<Float>,
+}
+
+impl RecurrentLayer {
+    pub fn random(rng: &mut dyn RngCore, inputs: usize, topology: &LayerTopology) -> Self {
+        Self::new(DenseLayer::random(rng, inputs + topology.neurons, topology))
+    }
+
+    pub fn from_weights(
+        inputs: usize,
+        topology: &LayerTopology,
+        weights: &mut dyn Iterator<Item = Float>,
+    ) -> Self {
+        Self::new(DenseLayer::from_weights(
+            inputs + topology.neurons,
+            topology,
+            weights,
+        ))
+    }
+
+    fn new(params: DenseLayer) -> Self {
+        Self { params }
+    }
+
+    /// Returns the state of a layer that has never been propagated.
+    pub fn state(&self) -> RecurrentState {
+        RecurrentState {
+            context: vec![0.0; self.params.inputs()],
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.params.inputs() - self.outputs()
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.params.outputs()
+    }
+
+    pub fn propagate_into(
+        &self,
+        state: &mut RecurrentState,
+        inputs: &[Float],
+        outputs: &mut Vec<Float>,
+    ) {
+        assert_eq!(inputs.len(), self.inputs());
+
+        let context = &mut state.context;
+
+        context[..inputs.len()].copy_from_slice(inputs);
+        self.params.propagate_into(context, outputs);
+        context[inputs.len()..].copy_from_slice(outputs);
+    }
+
+    pub fn params(&self) -> &DenseLayer {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut DenseLayer {
+        &mut self.params
+    }
+}
+
+impl RecurrentState {
+    /// Forgets the previous outputs, as if the layer has never been
+    /// propagated.
+    pub fn reset(&mut self) {
+        self.context.fill(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn topology(neurons: usize) -> LayerTopology {
+        LayerTopology {
+            neurons,
+            activation: Activation::Linear,
+            kind: LayerKind::Recurrent,
+            ..Default::default()
+        }
+    }
+
+    /// Single neuron with bias 0.0, weight 1.0 for its input and 0.5 for its
+    /// own previous output
+    fn layer() -> RecurrentLayer {
+        RecurrentLayer::from_weights(1, &topology(1), &mut [0.0, 1.0, 0.5].into_iter())
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn remembers_previous_outputs() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            for (inputs, expected) in [(1.0, 1.0), (1.0, 1.5), (0.0, 0.75), (0.0, 0.375)] {
+                layer.propagate_into(&mut state, &[inputs], &mut outputs);
+
+                assert_relative_eq!(outputs[0], expected);
+            }
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_wrong_input_size() {
+            let layer = layer();
+
+            layer.propagate_into(&mut layer.state(), &[1.0, 2.0], &mut Vec::new());
+        }
+    }
+
+    mod reset {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layer = layer();
+            let mut state = layer.state();
+            let mut outputs = Vec::new();
+
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+            state.reset();
+            layer.propagate_into(&mut state, &[1.0], &mut outputs);
+
+            assert_relative_eq!(outputs.as_slice(), [1.0].as_ref());
+        }
+    }
+
+    mod random {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layer = RecurrentLayer::random(&mut rng, 2, &topology(3));
+
+            assert_eq!(layer.inputs(), 2);
+            assert_eq!(layer.outputs(), 3);
+
+            // Each neuron: bias + 2 input weights + 3 recurrent weights
+            assert_eq!(layer.params().weights().count(), 3 * (1 + 2 + 3));
+        }
+    }
+}
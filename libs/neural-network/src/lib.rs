@@ -1,269 +1,3605 @@
-use rand::Rng;
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+mod activation;
+mod builder;
+
+#[cfg(feature = "std")]
+mod checkpoint;
+
+mod compare;
+mod display;
+mod dot;
+mod ensemble;
+mod fixed;
+mod graphviz;
+mod half;
+mod init;
+mod layer;
+
+#[cfg(not(feature = "std"))]
+mod math;
+
+mod normalizer;
+
+#[cfg(feature = "npz")]
+mod npz;
+
+#[cfg(feature = "onnx")]
+mod onnx;
+
+mod quantize;
+
+#[cfg(feature = "train")]
+mod train;
+
+pub use self::ensemble::{Combine, Ensemble};
+pub use self::fixed::FixedNetwork;
+pub use self::half::HalfNetwork;
+pub use self::layer::{Convolution, CustomLayer};
+pub use self::normalizer::Normalizer;
+pub use self::quantize::QuantizedNetwork;
+pub use self::{activation::*, builder::*, init::*};
+use self::{dot::dot, layer::*};
+use alloc::{format, string::String, vec, vec::Vec};
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use thiserror::Error;
+
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use self::math::FloatExt;
+
+/// Precision used for all of the network's inputs, outputs and parameters;
+/// `f32` by default, `f64` with the `f64` feature (e.g. to check whether
+/// rounding affects the evolved behavior).
+#[cfg(not(feature = "f64"))]
+pub type Float = f32;
+
+#[cfg(feature = "f64")]
+pub type Float = f64;
+
+/// Relative tolerance for comparing against constants written down with `f32`
+/// precision, so that tests pass with the `f64` feature as well.
+#[cfg(test)]
+const TOLERANCE: Float = if cfg!(feature = "f64") {
+    1e-6
+} else {
+    Float::EPSILON
+};
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Network {
     layers: Vec<Layer>,
+
+    /// Dropout rate of each layer, see [`LayerTopology::dropout`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    dropout: Vec<f32>,
+
+    /// Skip connection of each layer, see [`LayerTopology::skip`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    skips: Vec<Option<usize>>,
+
+    /// Rescales inputs before the first layer, see [`Self::set_normalizer()`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    normalizer: Option<Normalizer>,
+
+    /// Decides which outputs get dropped; present only in training mode
+    #[cfg_attr(feature = "serde", serde(skip))]
+    training: Option<StdRng>,
+
+    /// See [`Self::enable_input_noise()`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    noise: Option<InputNoise>,
+}
+
+/// Gaussian noise added to a network's inputs.
+#[derive(Clone, Debug)]
+struct InputNoise {
+    distribution: Normal<f32>,
+    rng: StdRng,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LayerTopology {
+    pub neurons: usize,
+
+    /// Activation used by this layer's neurons; ignored for the input layer
+    /// (i.e. the first topology passed to [`Network::random()`]).
+    pub activation: Activation,
+
+    /// Type of this layer; ignored for the input layer.
+    pub kind: LayerKind,
+
+    /// Distribution this layer's parameters are drawn from by
+    /// [`Network::random()`]; ignored for the input layer.
+    pub init: Init,
+
+    /// Probability of zeroing each of this layer's outputs while the network
+    /// is in training mode (see [`Network::enable_training()`]), with the
+    /// remaining outputs scaled up to compensate; ignored for the input layer.
+    pub dropout: f32,
+
+    /// Earlier layer (counting the input layer as 0, like the slice passed to
+    /// [`Network::random()`]) whose outputs get added to this layer's
+    /// weighted sums before the activation, i.e. a skip connection; it must
+    /// have as many neurons as this layer, which has to be dense.
+    pub skip: Option<usize>,
+
+    /// Whether this layer's neurons have biases (true by default); without
+    /// them, the layer's genome consists only of the weights. Ignored for
+    /// the input layer.
+    pub bias: bool,
+
+    /// Whether this layer's weighted sums get normalized (to zero mean and
+    /// unit variance across the layer's neurons) before the activation, which
+    /// keeps activations in range however the weights get scaled; ignored
+    /// for LSTM and GRU layers, and for the input layer.
+    pub normalize: bool,
+
+    /// Which of this dense layer's neurons are connected to which inputs,
+    /// laid out like its weight matrix (`mask[neuron * inputs + input]`),
+    /// e.g. to wire a retina's receptors only to nearby neurons; weights of
+    /// missing connections are fixed at zero and don't appear in the genome.
+    /// Ignored for the input layer.
+    pub mask: Option<Vec<bool>>,
+}
+
+impl Default for LayerTopology {
+    fn default() -> Self {
+        Self {
+            neurons: 0,
+            activation: Activation::default(),
+            kind: LayerKind::default(),
+            init: Init::default(),
+            dropout: 0.0,
+            skip: None,
+            bias: true,
+            normalize: false,
+            mask: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayerKind {
+    /// Fully-connected layer
+    #[default]
+    Dense,
+
+    /// Fully-connected layer which also sees its own outputs from the previous
+    /// call to [`Network::propagate_with()`], until [`Network::reset_state()`]
+    Recurrent,
+
+    /// Long short-term memory layer - like [`Self::Recurrent`], but with
+    /// gates deciding what to remember and for how long; the topology's
+    /// activation is ignored (gates use sigmoid, cells use tanh)
+    Lstm,
+
+    /// Gated recurrent unit layer - a lighter variant of [`Self::Lstm`]; the
+    /// topology's activation is ignored as well
+    Gru,
+
+    /// Convolutional layer, e.g. for feeding the network an image; its
+    /// number of neurons must match [`Convolution::outputs()`]
+    Convolution(Convolution),
+
+    /// Fully-connected layer whose weights strengthen between co-active
+    /// neurons as it's propagated (Hebbian plasticity), until
+    /// [`Network::reset_state()`]; each connection has its own learning rate,
+    /// laid out after the layer's weights and biases in the genome
+    Plastic,
+
+    /// Layer implemented outside of this crate, see [`CustomLayer`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom,
+}
+
+/// Parameters of a dense layer, see [`Network::from_layers()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerWeights {
+    /// Bias of each neuron
+    pub biases: Vec<Float>,
+
+    /// Weights of each neuron (row `n` for the `n`-th neuron), one per input
+    pub weights: Vec<Vec<Float>>,
+
+    pub activation: Activation,
+}
+
+/// Report of [`Network::prune()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sparsity {
+    /// Number of weights zeroed by the call
+    pub pruned: usize,
+
+    /// Number of weights that are zero now, including ones that were zero
+    /// already
+    pub zeros: usize,
+
+    /// Number of weights overall (biases aren't counted)
+    pub total: usize,
+}
+
+impl Sparsity {
+    /// Returns the fraction of weights that are zero.
+    pub fn ratio(&self) -> f32 {
+        self.zeros as f32 / self.total.max(1) as f32
+    }
+}
+
+/// Scratch space reused across calls to [`Network::propagate_in_place()`]
+/// and [`Network::propagate_into()`], along with the [`NetworkState`] they
+/// continue from - so, like a state, a buffer belongs to a single network
+/// (e.g. one buffer per animal).
+///
+/// Buffers passed to [`Network::propagate_in_place_with()`] and
+/// [`Network::propagate_into_with()`] are just scratch space, which can be
+/// shared by any networks.
+#[derive(Clone, Debug, Default)]
+pub struct PropagateBuffer {
+    front: Vec<Float>,
+    back: Vec<Float>,
+
+    /// Outputs of layers which are sources of skip connections, indexed like
+    /// topologies
+    skips: Vec<Vec<Float>>,
+
+    /// Created by the first call that needs it
+    state: Option<NetworkState>,
+}
+
+impl PropagateBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the network's state (e.g. between simulations), so that the
+    /// next propagation behaves as if it was the first one.
+    pub fn reset_state(&mut self) {
+        self.state = None;
+    }
+}
+
+/// Memory of a network's stateful layers (e.g. [`LayerKind::Recurrent`])
+/// between calls to [`Network::propagate_with()`], see [`Network::state()`].
+///
+/// It's kept outside of the network, so that one network can drive many
+/// animals (each with its own state) and stay `Sync` while doing that.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkState {
+    layers: Vec<LayerState>,
+    rngs: Rngs,
+}
+
+/// Randomness drawn by a propagation, kept in [`NetworkState`] so that
+/// consecutive calls don't repeat it; each RNG starts as a copy of the
+/// network's.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Rngs {
+    /// See [`Network::enable_training()`]
+    dropout: Option<StdRng>,
+
+    /// See [`Network::enable_input_noise()`]
+    noise: Option<StdRng>,
+}
+
+/// Outputs of each layer, see [`Network::propagate_trace()`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trace {
+    /// Inputs (normalized, if the network has a [`Normalizer`]), followed by
+    /// outputs of each layer
+    activations: Vec<Vec<Float>>,
+}
+
+impl Trace {
+    pub fn inputs(&self) -> &[Float] {
+        &self.activations[0]
+    }
+
+    /// Returns outputs of given layer (counting from the first one after the
+    /// inputs, same as e.g. [`Network::bias()`]), after its activation and
+    /// dropout.
+    pub fn layer(&self, layer: usize) -> Option<&[Float]> {
+        self.activations.get(layer + 1).map(Vec::as_slice)
+    }
+
+    /// Returns outputs of each layer, in order.
+    pub fn layers(&self) -> impl Iterator<Item = &[Float]> {
+        self.activations[1..].iter().map(Vec::as_slice)
+    }
+
+    /// Returns the number of layers, not counting the inputs.
+    pub fn len(&self) -> usize {
+        self.activations.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum NetworkError {
+    #[error("a network needs at least 2 layers (input and output), got {actual}")]
+    NotEnoughLayers { actual: usize },
+
+    #[error("expected {expected} inputs, got {actual}")]
+    InputSizeMismatch { expected: usize, actual: usize },
+
+    #[error("expected {expected} outputs, got {actual}")]
+    OutputSizeMismatch { expected: usize, actual: usize },
+
+    #[error("no layer #{layer}")]
+    LayerOutOfBounds { layer: usize },
+
+    #[error("no neuron #{neuron} in layer #{layer}")]
+    NeuronOutOfBounds { layer: usize, neuron: usize },
+
+    #[error("layer #{layer} has no biases")]
+    NoBias { layer: usize },
+
+    #[error("no weight #{index} in neuron #{neuron} of layer #{layer}")]
+    WeightOutOfBounds {
+        layer: usize,
+        neuron: usize,
+        index: usize,
+    },
+
+    #[error("convolution of layer #{layer} doesn't fit its inputs and neurons")]
+    InvalidConvolution { layer: usize },
+
+    #[error("dropout of layer #{layer} must be in [0, 1)")]
+    InvalidDropout { layer: usize },
+
+    #[error("skip connection of layer #{layer} must come from an earlier layer of the same size into a dense layer")]
+    InvalidSkip { layer: usize },
+
+    #[error("expected {expected} weights, got {actual}")]
+    WeightCountMismatch { expected: usize, actual: usize },
+
+    #[error("networks have different topologies")]
+    TopologyMismatch,
+
+    #[error("layer #{layer} isn't supported by this operation")]
+    UnsupportedLayer { layer: usize },
+
+    #[error("layer #{layer} can't lose its last neuron")]
+    LastNeuron { layer: usize },
+
+    #[error("mask of layer #{layer} must cover all weights of a dense layer")]
+    InvalidMask { layer: usize },
+
+    #[error("biases and weights of layer #{layer} don't form a matrix")]
+    InvalidLayerWeights { layer: usize },
+
+    #[error("an ensemble needs at least 1 network")]
+    EmptyEnsemble,
+
+    #[error("weight #{index} of neuron #{neuron} in layer #{layer} is masked out")]
+    MaskedWeight {
+        layer: usize,
+        neuron: usize,
+        index: usize,
+    },
+
+    #[error("layer #{layer} has too many parameters")]
+    TooManyParameters { layer: usize },
 }
 
-pub struct LayerTopology {
-    pub neurons: usize,
-}
+impl Network {
+    /// Propagates `inputs` through the network, returning outputs of its last
+    /// layer.
+    ///
+    /// Stateful layers start from a blank state with each call, as if the
+    /// network has never been propagated - to let them remember previous
+    /// calls, see [`Self::propagate_with()`].
+    ///
+    /// Neither dropout (see [`Self::enable_training()`]) nor input noise (see
+    /// [`Self::enable_input_noise()`]) gets applied, as without a state,
+    /// there'd be nothing for them to draw from.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn propagate(&self, inputs: Vec<Float>) -> Vec<Float> {
+        self.activations(None, inputs).pop().unwrap()
+    }
+
+    /// Like [`Self::propagate()`], but stateful layers (e.g. recurrent ones)
+    /// continue from `state` and leave their memory of this call in it; so
+    /// do the RNGs of the dropout and the input noise.
+    ///
+    /// Panics if `state` belongs to a network of a different topology.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn propagate_with(&self, state: &mut NetworkState, inputs: Vec<Float>) -> Vec<Float> {
+        self.activations(Some(state), inputs).pop().unwrap()
+    }
+
+    /// Returns the state of a network that has never been propagated, for
+    /// [`Self::propagate_with()`].
+    pub fn state(&self) -> NetworkState {
+        NetworkState {
+            layers: self.layers.iter().map(Layer::state).collect(),
+            rngs: Rngs::default(),
+        }
+    }
+
+    /// Like [`Self::propagate()`], but also returns outputs of each layer
+    /// along the way, e.g. to visualize activity of the neurons.
+    pub fn propagate_trace(&self, inputs: Vec<Float>) -> (Vec<Float>, Trace) {
+        let activations = self.activations(None, inputs);
+        let outputs = activations[activations.len() - 1].clone();
+
+        (outputs, Trace { activations })
+    }
+
+    /// Propagates `inputs` (starting from `state`, if there's one), returning
+    /// them (normalized) followed by outputs of each layer.
+    fn activations(&self, state: Option<&mut NetworkState>, inputs: Vec<Float>) -> Vec<Vec<Float>> {
+        self.check_state(state.as_deref());
+
+        let (mut layers, mut rngs) = match state {
+            Some(NetworkState { layers, rngs }) => (Some(layers.as_mut_slice()), Some(rngs)),
+            None => (None, None),
+        };
+
+        // Outputs of each layer, input layer included, as skip connections
+        // can refer back to any of them
+        let mut activations = vec![inputs];
+
+        self.preprocess(rngs.as_deref_mut(), &mut activations[0]);
+
+        for (id, layer) in self.layers.iter().enumerate() {
+            let mut outputs = Vec::with_capacity(layer.outputs());
+            let skip = self.skip(id).map(|from| activations[from].as_slice());
+
+            self.propagate_layer(
+                layers.as_deref_mut(),
+                id,
+                &activations[id],
+                skip,
+                &mut outputs,
+            );
+            self.dropout(rngs.as_deref_mut(), id, &mut outputs);
+            activations.push(outputs);
+        }
+
+        activations
+    }
+
+    /// Turns on training mode, in which layers apply their dropout; `seed`
+    /// decides which outputs get dropped.
+    ///
+    /// Like input noise, dropout is drawn from a copy of the network's RNG
+    /// kept in each state, and it's skipped by paths without a state, such as
+    /// [`Self::propagate()`]; training with `fit()` applies it all the same.
+    pub fn enable_training(&mut self, seed: u64) {
+        self.training = Some(StdRng::seed_from_u64(seed));
+    }
+
+    pub fn disable_training(&mut self) {
+        self.training = None;
+    }
+
+    pub fn is_training(&self) -> bool {
+        self.training.is_some()
+    }
+
+    /// Makes the network rescale its inputs with given normalizer (e.g. one
+    /// fitted to observations recorded during a simulation) before they reach
+    /// the first layer, or stop rescaling them when `None`.
+    ///
+    /// Panics if the normalizer is for a different number of inputs.
+    pub fn set_normalizer(&mut self, normalizer: Option<Normalizer>) {
+        if let Some(normalizer) = &normalizer {
+            assert_eq!(
+                normalizer.len(),
+                self.input_size(),
+                "normalizer is for a different number of inputs"
+            );
+        }
+
+        self.normalizer = normalizer;
+    }
+
+    pub fn normalizer(&self) -> Option<&Normalizer> {
+        self.normalizer.as_ref()
+    }
+
+    /// Makes the network add Gaussian noise with standard deviation `sigma`
+    /// to each of its inputs (before the normalizer, if there's one)
+    /// whenever it's propagated with a state, e.g. to evolve controllers that
+    /// cope with noisy senses; the noise is drawn from an RNG seeded from
+    /// `rng`, so that runs stay reproducible.
+    ///
+    /// Each state (see [`Self::propagate_with()`] and
+    /// [`Self::propagate_in_place()`]) draws the noise from its own copy of
+    /// that RNG, so that consecutive calls get different noise; paths without
+    /// a state, such as [`Self::propagate()`], don't add any noise.
+    ///
+    /// Panics if `sigma` is negative or not finite.
+    pub fn enable_input_noise(&mut self, sigma: f32, rng: &mut dyn RngCore) {
+        assert!(
+            sigma.is_finite() && sigma >= 0.0,
+            "sigma must be finite and non-negative"
+        );
+
+        let distribution = Normal::new(0.0, sigma).unwrap();
+
+        self.noise = Some(InputNoise {
+            distribution,
+            rng: StdRng::seed_from_u64(rng.next_u64()),
+        });
+    }
+
+    pub fn disable_input_noise(&mut self) {
+        self.noise = None;
+    }
+
+    /// Returns the standard deviation of the input noise, if it's enabled.
+    pub fn input_noise(&self) -> Option<f32> {
+        self.noise
+            .as_ref()
+            .map(|noise| noise.distribution.std_dev())
+    }
+
+    /// Applies the input noise (if there's something to draw it from) and
+    /// the normalizer, if the network has them.
+    fn preprocess(&self, rngs: Option<&mut Rngs>, inputs: &mut [Float]) {
+        if let (Some(noise), Some(rngs)) = (&self.noise, rngs) {
+            let rng = rngs.noise.get_or_insert_with(|| noise.rng.clone());
+
+            for input in inputs.iter_mut() {
+                *input += Float::from(noise.distribution.sample(rng));
+            }
+        }
+
+        if let Some(normalizer) = &self.normalizer {
+            normalizer.normalize(inputs);
+        }
+    }
+
+    /// Clears `state` (e.g. between simulations), so that the next
+    /// propagation behaves as if it was the first one.
+    pub fn reset_state(&self, state: &mut NetworkState) {
+        self.check_state(Some(state));
+
+        for state in &mut state.layers {
+            state.reset();
+        }
+
+        state.rngs = Rngs::default();
+    }
+
+    fn check_state(&self, state: Option<&NetworkState>) {
+        if let Some(state) = state {
+            assert_eq!(
+                state.layers.len(),
+                self.layers.len(),
+                "state belongs to a network of a different topology"
+            );
+        }
+    }
+
+    /// Like [`Self::propagate_with()`], but keeps intermediate outputs in
+    /// `buffer` instead of allocating a new vector per layer, and the state
+    /// in `buffer` too (see [`PropagateBuffer`]); once the buffer has grown
+    /// to the widest layer, propagating doesn't allocate at all.
+    ///
+    /// Panics if `buffer` keeps the state of a network of a different
+    /// topology.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn propagate_in_place<'a>(
+        &self,
+        inputs: &[Float],
+        buffer: &'a mut PropagateBuffer,
+    ) -> &'a [Float] {
+        let PropagateBuffer {
+            front,
+            back,
+            skips,
+            state,
+        } = buffer;
+
+        let state = state.get_or_insert_with(|| self.state());
+
+        self.propagate_buffered(state, inputs, front, back, skips);
+        front
+    }
+
+    /// Like [`Self::propagate_in_place()`], but continues from `state`
+    /// instead of the state kept in `buffer`.
+    ///
+    /// Panics if `state` belongs to a network of a different topology.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn propagate_in_place_with<'a>(
+        &self,
+        state: &mut NetworkState,
+        inputs: &[Float],
+        buffer: &'a mut PropagateBuffer,
+    ) -> &'a [Float] {
+        let PropagateBuffer {
+            front, back, skips, ..
+        } = buffer;
+
+        self.propagate_buffered(state, inputs, front, back, skips);
+        front
+    }
+
+    /// Propagates `inputs` into `front`, using `back` and `skips` as scratch
+    /// space.
+    fn propagate_buffered(
+        &self,
+        state: &mut NetworkState,
+        inputs: &[Float],
+        front: &mut Vec<Float>,
+        back: &mut Vec<Float>,
+        skips: &mut Vec<Vec<Float>>,
+    ) {
+        self.check_state(Some(state));
+
+        let NetworkState { layers, rngs } = state;
+
+        front.clear();
+        front.extend_from_slice(inputs);
+        self.preprocess(Some(rngs), front);
+        skips.resize_with(self.layers.len(), Vec::new);
+
+        for id in 0..self.layers.len() {
+            if self.skips.contains(&Some(id)) {
+                skips[id].clone_from(front);
+            }
+
+            let skip = self.skip(id).map(|from| skips[from].as_slice());
+
+            self.propagate_layer(Some(layers), id, front, skip, back);
+            self.dropout(Some(rngs), id, back);
+            core::mem::swap(front, back);
+        }
+    }
+
+    /// Like [`Self::propagate_in_place()`], but writes the outputs into
+    /// `outputs` (e.g. straight into the buffer driving the animal's speed
+    /// and rotation) instead of returning them.
+    ///
+    /// Panics if `outputs` doesn't match the size of the output layer.
+    pub fn propagate_into(
+        &self,
+        inputs: &[Float],
+        outputs: &mut [Float],
+        buffer: &mut PropagateBuffer,
+    ) {
+        assert_eq!(outputs.len(), self.output_size());
+
+        outputs.copy_from_slice(self.propagate_in_place(inputs, buffer));
+    }
+
+    /// Like [`Self::propagate_into()`], but continues from `state` instead of
+    /// the state kept in `buffer`.
+    ///
+    /// Panics if `outputs` doesn't match the size of the output layer, or if
+    /// `state` belongs to a network of a different topology.
+    pub fn propagate_into_with(
+        &self,
+        state: &mut NetworkState,
+        inputs: &[Float],
+        outputs: &mut [Float],
+        buffer: &mut PropagateBuffer,
+    ) {
+        assert_eq!(outputs.len(), self.output_size());
+
+        outputs.copy_from_slice(self.propagate_in_place_with(state, inputs, buffer));
+    }
+
+    /// Propagates many inputs at once (e.g. observations of the whole
+    /// population), returning outputs in the same order.
+    ///
+    /// The batch goes through the network layer by layer rather than input by
+    /// input, so each layer's weights stay in cache while all of the inputs
+    /// pass through it.
+    ///
+    /// Each input starts from a blank state, same as with
+    /// [`Self::propagate()`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn propagate_batch(&self, inputs: &[Vec<Float>]) -> Vec<Vec<Float>> {
+        // Same as in `propagate()`, but for the whole batch
+        let mut activations = vec![inputs.to_vec()];
+
+        for inputs in &mut activations[0] {
+            self.preprocess(None, inputs);
+        }
+
+        for (id, layer) in self.layers.iter().enumerate() {
+            let outputs = activations[id]
+                .iter()
+                .enumerate()
+                .map(|(sample, inputs)| {
+                    let mut outputs = Vec::with_capacity(layer.outputs());
+                    let skip = self
+                        .skip(id)
+                        .map(|from| activations[from][sample].as_slice());
+
+                    self.propagate_layer(None, id, inputs, skip, &mut outputs);
+                    self.dropout(None, id, &mut outputs);
+                    outputs
+                })
+                .collect();
+
+            activations.push(outputs);
+        }
+
+        activations.pop().unwrap()
+    }
+
+    /// Like [`Self::propagate()`], but returns an error instead of panicking
+    /// when `inputs` doesn't match the size of the input layer.
+    pub fn try_propagate(&self, inputs: Vec<Float>) -> Result<Vec<Float>, NetworkError> {
+        let expected = self.input_size();
+
+        if inputs.len() != expected {
+            return Err(NetworkError::InputSizeMismatch {
+                expected,
+                actual: inputs.len(),
+            });
+        }
+
+        Ok(self.propagate(inputs))
+    }
+
+    pub fn random(rng: &mut dyn RngCore, layers: &[LayerTopology]) -> Self {
+        assert!(layers.len() > 1);
+
+        let network = Self::new(
+            layers
+                .windows(2)
+                .map(|layers| Layer::random(rng, layers[0].neurons, &layers[1]))
+                .collect(),
+        );
+
+        network.with_topology(layers)
+    }
+
+    /// Like [`Self::random()`], but returns an error instead of panicking
+    /// when there are fewer than two layers, when a dropout rate is out of
+    /// range, when a convolution doesn't fit its layer or when a skip
+    /// connection is invalid.
+    pub fn try_random(
+        rng: &mut dyn RngCore,
+        layers: &[LayerTopology],
+    ) -> Result<Self, NetworkError> {
+        Self::validate(layers)?;
+
+        Ok(Self::random(rng, layers))
+    }
+
+    /// Like [`Self::random()`], but draws all of the parameters from
+    /// `distribution` instead of each layer's [`LayerTopology::init`], e.g.:
+    ///
+    /// ```
+    /// # use lib_neural_network::{Network, NetworkBuilder};
+    /// # use rand::SeedableRng;
+    /// # let mut rng = rand_chacha::ChaCha8Rng::from_seed(Default::default());
+    /// let builder = NetworkBuilder::new(16).layer(8).layer(2);
+    /// let cauchy = rand_distr::Cauchy::new(0.0, 0.1).unwrap();
+    /// let network = Network::random_with(&mut rng, builder.topology(), cauchy);
+    /// ```
+    pub fn random_with(
+        rng: &mut dyn RngCore,
+        layers: &[LayerTopology],
+        distribution: impl Distribution<f32>,
+    ) -> Self {
+        assert!(layers.len() > 1);
+
+        let weights = core::iter::repeat_with(|| Float::from(distribution.sample(rng)))
+            .take(Self::num_parameters_of(layers));
+
+        Self::from_weights(layers, weights)
+    }
+
+    /// Builds a network from genes produced by [`Self::weights()`].
+    ///
+    /// Panics if `weights` yields fewer or more values than `layers` need.
+    pub fn from_weights(
+        layers: &[LayerTopology],
+        weights: impl IntoIterator<Item = Float>,
+    ) -> Self {
+        assert!(layers.len() > 1);
+
+        let mut weights = weights.into_iter();
+
+        let network = Self::new(
+            layers
+                .windows(2)
+                .map(|layers| Layer::from_weights(layers[0].neurons, &layers[1], &mut weights))
+                .collect(),
+        );
+
+        if weights.next().is_some() {
+            panic!("got too many weights");
+        }
+
+        network.with_topology(layers)
+    }
+
+    /// Like [`Self::from_weights()`], but returns an error instead of
+    /// panicking when `layers` are invalid (see [`Self::try_random()`]) or
+    /// when `weights` doesn't match what they need.
+    pub fn try_from_weights(
+        layers: &[LayerTopology],
+        weights: impl IntoIterator<Item = Float>,
+    ) -> Result<Self, NetworkError> {
+        Self::validate(layers)?;
+
+        let weights: Vec<_> = weights.into_iter().collect();
+
+        let expected = Self::num_parameters_of(layers);
+
+        if weights.len() != expected {
+            return Err(NetworkError::WeightCountMismatch {
+                expected,
+                actual: weights.len(),
+            });
+        }
+
+        Ok(Self::from_weights(layers, weights))
+    }
+
+    /// Builds a network of dense layers out of their biases and weight
+    /// matrices, e.g. to import a network trained elsewhere or to set up an
+    /// exact one in a test, without having to lay the parameters out like
+    /// [`Self::weights()`] does.
+    ///
+    /// Panics if there are no layers, if biases and rows of a layer don't
+    /// match or if a layer's rows aren't as long as the previous layer is
+    /// wide.
+    pub fn from_layers(layers: Vec<LayerWeights>) -> Self {
+        match Self::try_from_layers(layers) {
+            Ok(network) => network,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Like [`Self::from_layers()`], but returns an error instead of
+    /// panicking.
+    pub fn try_from_layers(layers: Vec<LayerWeights>) -> Result<Self, NetworkError> {
+        // Without layers there's no input layer either, as its size comes
+        // from the first layer's weights
+        if layers.is_empty() {
+            return Err(NetworkError::NotEnoughLayers { actual: 0 });
+        }
+
+        for (layer, weights) in layers.iter().enumerate() {
+            let inputs = weights.weights.first().map_or(0, Vec::len);
+
+            if weights.weights.is_empty()
+                || weights.biases.len() != weights.weights.len()
+                || weights.weights.iter().any(|row| row.len() != inputs)
+            {
+                return Err(NetworkError::InvalidLayerWeights { layer });
+            }
+
+            if let Some(previous) = layer.checked_sub(1) {
+                let expected = layers[previous].biases.len();
+
+                if inputs != expected {
+                    return Err(NetworkError::InputSizeMismatch {
+                        expected,
+                        actual: inputs,
+                    });
+                }
+            }
+        }
+
+        Ok(Self::new(
+            layers
+                .into_iter()
+                .map(|layer| {
+                    Layer::Dense(DenseLayer::new(
+                        layer.biases,
+                        layer.weights,
+                        layer.activation,
+                    ))
+                })
+                .collect(),
+        ))
+    }
+
+    /// Inserts a [`CustomLayer`] before the `index`-th layer (layers are
+    /// counted like in [`Self::bias()`]), or at the end of the network when
+    /// `index` is the number of layers.
+    ///
+    /// The layer's parameters get appended to the genome at its position, see
+    /// [`Self::weights()`]; skip connections are kept, still connecting the
+    /// layers they did.
+    ///
+    /// Panics if `index` is out of bounds or if the layer doesn't fit between
+    /// its neighbours.
+    pub fn insert_layer(&mut self, index: usize, layer: impl CustomLayer + 'static) {
+        if let Err(err) = self.try_insert_layer(index, layer) {
+            panic!("{err}");
+        }
+    }
+
+    /// Like [`Self::insert_layer()`], but returns an error instead of
+    /// panicking.
+    pub fn try_insert_layer(
+        &mut self,
+        index: usize,
+        layer: impl CustomLayer + 'static,
+    ) -> Result<(), NetworkError> {
+        if index > self.layers.len() {
+            return Err(NetworkError::LayerOutOfBounds { layer: index });
+        }
+
+        let expected = match index {
+            0 => self.input_size(),
+            index => self.layers[index - 1].outputs(),
+        };
+
+        if layer.inputs() != expected {
+            return Err(NetworkError::InputSizeMismatch {
+                expected,
+                actual: layer.inputs(),
+            });
+        }
+
+        if let Some(next) = self.layers.get(index) {
+            if layer.outputs() != next.inputs() {
+                return Err(NetworkError::OutputSizeMismatch {
+                    expected: next.inputs(),
+                    actual: layer.outputs(),
+                });
+            }
+        }
+
+        // Outputs of layers from `index` onwards move one place further
+        for from in self.skips.iter_mut().flatten() {
+            if *from > index {
+                *from += 1;
+            }
+        }
+
+        self.layers
+            .insert(index, Layer::Custom(alloc::boxed::Box::new(layer)));
+        self.dropout.insert(index, 0.0);
+        self.skips.insert(index, None);
+
+        Ok(())
+    }
+
+    /// Adds a neuron to given hidden layer, e.g. as a structural mutation
+    /// that evolves the topology rather than just the weights.
+    ///
+    /// The neuron's bias and incoming weights get drawn from
+    /// [`Init::default()`], while its outgoing weights start at zero, so that
+    /// (unless the layer normalizes) the network propagates the same as
+    /// before until those get mutated.
+    ///
+    /// Panics if `layer` isn't a dense hidden layer followed by a dense layer,
+    /// if either of them is masked or if it's an end of a skip connection.
+    pub fn grow_neuron(&mut self, layer: usize, rng: &mut dyn RngCore) {
+        if let Err(err) = self.try_grow_neuron(layer, rng) {
+            panic!("{err}");
+        }
+    }
+
+    /// Like [`Self::grow_neuron()`], but returns an error instead of
+    /// panicking.
+    pub fn try_grow_neuron(
+        &mut self,
+        layer: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<(), NetworkError> {
+        let (this, next) = self.resizable(layer)?;
+
+        this.push_neuron(Init::default().sampler(rng, this.inputs(), this.outputs() + 1));
+        next.push_input();
+
+        Ok(())
+    }
+
+    /// Removes given neuron from given hidden layer, along with the weights
+    /// the next layer had for it - the counterpart of [`Self::grow_neuron()`].
+    ///
+    /// Panics if the neuron doesn't exist or is the layer's last one, or if
+    /// the layer can't be resized, see [`Self::grow_neuron()`].
+    pub fn shrink_neuron(&mut self, layer: usize, neuron: usize) {
+        if let Err(err) = self.try_shrink_neuron(layer, neuron) {
+            panic!("{err}");
+        }
+    }
+
+    /// Like [`Self::shrink_neuron()`], but returns an error instead of
+    /// panicking.
+    pub fn try_shrink_neuron(&mut self, layer: usize, neuron: usize) -> Result<(), NetworkError> {
+        let (this, next) = self.resizable(layer)?;
+
+        if neuron >= this.outputs() {
+            return Err(NetworkError::NeuronOutOfBounds { layer, neuron });
+        }
+
+        if this.outputs() == 1 {
+            return Err(NetworkError::LastNeuron { layer });
+        }
+
+        this.remove_neuron(neuron);
+        next.remove_input(neuron);
+
+        Ok(())
+    }
+
+    /// Returns given hidden layer and the one after it, provided the former
+    /// can change its number of neurons.
+    fn resizable(
+        &mut self,
+        layer: usize,
+    ) -> Result<(&mut DenseLayer, &mut DenseLayer), NetworkError> {
+        if layer >= self.layers.len() {
+            return Err(NetworkError::LayerOutOfBounds { layer });
+        }
+
+        // Skip connections need both of their ends to be of the same size
+        let skipped = self.skip(layer).is_some() || self.skips.contains(&Some(layer + 1));
+
+        if layer + 1 == self.layers.len() || skipped {
+            return Err(NetworkError::UnsupportedLayer { layer });
+        }
+
+        // Masks would have to grow along with the weights
+        match &mut self.layers[layer..=layer + 1] {
+            [Layer::Dense(this), _] if this.mask().is_some() => {
+                Err(NetworkError::UnsupportedLayer { layer })
+            }
+
+            [Layer::Dense(_), Layer::Dense(next)] if next.mask().is_some() => {
+                Err(NetworkError::UnsupportedLayer { layer: layer + 1 })
+            }
+
+            [Layer::Dense(this), Layer::Dense(next)] => Ok((this, next)),
+            [Layer::Dense(_), _] => Err(NetworkError::UnsupportedLayer { layer: layer + 1 }),
+            _ => Err(NetworkError::UnsupportedLayer { layer }),
+        }
+    }
+
+    /// Returns how many parameters a network of given topology has.
+    fn num_parameters_of(layers: &[LayerTopology]) -> usize {
+        layers
+            .windows(2)
+            .map(|layers| Layer::num_parameters(layers[0].neurons, &layers[1]))
+            .sum()
+    }
+
+    fn new(layers: Vec<Layer>) -> Self {
+        Self {
+            dropout: vec![0.0; layers.len()],
+            skips: vec![None; layers.len()],
+            layers,
+            normalizer: None,
+            training: None,
+            noise: None,
+        }
+    }
+
+    /// Applies the parts of `layers` that live outside of the layers
+    /// themselves, i.e. dropout and skip connections.
+    fn with_topology(mut self, layers: &[LayerTopology]) -> Self {
+        for layer in 0..self.layers.len() {
+            assert!(
+                Self::skip_is_valid(layers, layer),
+                "skip connection of layer #{layer} is invalid"
+            );
+
+            assert!(
+                Self::mask_is_valid(layers, layer),
+                "mask of layer #{layer} is invalid"
+            );
+        }
+
+        self.dropout = layers[1..].iter().map(|layer| layer.dropout).collect();
+        self.skips = layers[1..].iter().map(|layer| layer.skip).collect();
+        self
+    }
+
+    /// Propagates `inputs` through given layer, adding `skip` - outputs of
+    /// the layer's skip connection, if it has one - before the activation.
+    ///
+    /// Without `state` (of each layer), stateful layers start from a blank
+    /// one.
+    fn propagate_layer(
+        &self,
+        state: Option<&mut [LayerState]>,
+        id: usize,
+        inputs: &[Float],
+        skip: Option<&[Float]>,
+        outputs: &mut Vec<Float>,
+    ) {
+        let layer = &self.layers[id];
+
+        match (skip, state) {
+            (Some(skip), _) => layer.propagate_skip_into(inputs, skip, outputs),
+            (None, Some(state)) => layer.propagate_into(&mut state[id], inputs, outputs),
+            (None, None) => layer.propagate_into(&mut layer.state(), inputs, outputs),
+        }
+    }
+
+    /// Returns the topology index of given layer's skip connection's source.
+    fn skip(&self, layer: usize) -> Option<usize> {
+        self.skips.get(layer).copied().flatten()
+    }
+
+    fn skip_is_valid(layers: &[LayerTopology], layer: usize) -> bool {
+        let topology = &layers[layer + 1];
+
+        topology.skip.is_none_or(|from| {
+            from <= layer
+                && layers[from].neurons == topology.neurons
+                && topology.kind == LayerKind::Dense
+        })
+    }
+
+    fn mask_is_valid(layers: &[LayerTopology], layer: usize) -> bool {
+        let topology = &layers[layer + 1];
+
+        topology.mask.as_ref().is_none_or(|mask| {
+            layers[layer].neurons.checked_mul(topology.neurons) == Some(mask.len())
+                && topology.kind == LayerKind::Dense
+        })
+    }
+
+    /// Applies given layer's dropout to its `outputs`, if the network is in
+    /// training mode (and there's something to draw the dropout from).
+    fn dropout(&self, rngs: Option<&mut Rngs>, layer: usize, outputs: &mut [Float]) {
+        let rate = self.dropout_rate(layer);
+
+        if let (Some(training), Some(rngs), true) = (&self.training, rngs, rate > 0.0) {
+            let rng = rngs.dropout.get_or_insert_with(|| training.clone());
+            let scale = Float::from(1.0 / (1.0 - rate));
+
+            for output in outputs {
+                *output = if rng.gen_bool(rate as _) {
+                    0.0
+                } else {
+                    *output * scale
+                };
+            }
+        }
+    }
+
+    fn dropout_rate(&self, layer: usize) -> f32 {
+        self.dropout.get(layer).copied().unwrap_or_default()
+    }
+
+    fn validate(layers: &[LayerTopology]) -> Result<(), NetworkError> {
+        if layers.len() < 2 {
+            return Err(NetworkError::NotEnoughLayers {
+                actual: layers.len(),
+            });
+        }
+
+        let mut parameters: usize = 0;
+
+        for (layer, topologies) in layers.windows(2).enumerate() {
+            if !(0.0..1.0).contains(&topologies[1].dropout) {
+                return Err(NetworkError::InvalidDropout { layer });
+            }
+
+            if topologies[1].kind == LayerKind::Custom {
+                return Err(NetworkError::UnsupportedLayer { layer });
+            }
+
+            parameters = Layer::checked_num_parameters(topologies[0].neurons, &topologies[1])
+                .and_then(|layer| parameters.checked_add(layer))
+                .ok_or(NetworkError::TooManyParameters { layer })?;
+
+            if let LayerKind::Convolution(convolution) = topologies[1].kind {
+                if convolution.outputs(topologies[0].neurons) != Some(topologies[1].neurons) {
+                    return Err(NetworkError::InvalidConvolution { layer });
+                }
+            }
+
+            if !Self::skip_is_valid(layers, layer) {
+                return Err(NetworkError::InvalidSkip { layer });
+            }
+
+            if !Self::mask_is_valid(layers, layer) {
+                return Err(NetworkError::InvalidMask { layer });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the topology this network could be rebuilt from (e.g. with
+    /// [`Self::from_weights()`]), input layer included.
+    ///
+    /// Layers don't remember how they were initialized, so each topology has
+    /// the default [`Init`].
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        let input = LayerTopology {
+            neurons: self.input_size(),
+            ..Default::default()
+        };
+
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(id, layer)| LayerTopology {
+                dropout: self.dropout_rate(id),
+                skip: self.skip(id),
+                ..layer.topology()
+            });
+
+        core::iter::once(input).chain(layers).collect()
+    }
+
+    /// Returns the number of values yielded by [`Self::weights()`].
+    pub fn num_parameters(&self) -> usize {
+        self.layers.iter().map(|layer| layer.num_weights()).sum()
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.layers[0].inputs()
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.layers[self.layers.len() - 1].outputs()
+    }
+
+    /// Returns all of the network's parameters as a flat sequence: layer by
+    /// layer, neuron by neuron - each neuron's bias (unless the layer has no
+    /// biases) followed by its weights - with the alpha of
+    /// [`Activation::Prelu`] layers after the layer's last neuron.
+    ///
+    /// This order is what genomes, [`Self::from_weights()`] and checkpoints
+    /// rely on, so it stays the same across versions.
+    pub fn weights(&self) -> impl Iterator<Item = Float> + '_ {
+        self.layers.iter().flat_map(|layer| layer.weights())
+    }
+
+    /// Returns all of the network's parameters in the same order as
+    /// [`Self::weights()`] does, e.g. so that mutation operators can modify
+    /// them in place.
+    pub fn weights_mut(&mut self) -> impl Iterator<Item = &mut Float> + '_ {
+        self.layers.iter_mut().flat_map(|layer| layer.weights_mut())
+    }
+
+    /// Nudges each parameter, with probability `rate`, by a random amount of
+    /// up to `magnitude` in either direction - same as `GaussianMutation`
+    /// from the genetic algorithm would do to [`Self::weights()`], but in
+    /// place.
+    pub fn mutate(&mut self, rng: &mut dyn RngCore, rate: f32, magnitude: f32) {
+        assert!((0.0..=1.0).contains(&rate), "rate must be in [0, 1]");
+
+        for weight in self.weights_mut() {
+            let sign = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+
+            if rng.gen_bool(rate as _) {
+                *weight += Float::from(sign * magnitude * rng.gen::<f32>());
+            }
+        }
+    }
+
+    /// Zeroes weights whose magnitude is below `threshold`, e.g. to find out
+    /// which inputs an evolved network actually relies on; biases (and custom
+    /// layers) are left intact.
+    pub fn prune(&mut self, threshold: Float) -> Sparsity {
+        let mut sparsity = Sparsity::default();
+
+        for layer in self.layers.iter_mut().filter(|layer| !layer.is_custom()) {
+            for weight in layer.params_mut().weight_matrix_mut() {
+                if *weight != 0.0 && weight.abs() < threshold {
+                    *weight = 0.0;
+                    sparsity.pruned += 1;
+                }
+
+                if *weight == 0.0 {
+                    sparsity.zeros += 1;
+                }
+
+                sparsity.total += 1;
+            }
+        }
+
+        sparsity
+    }
+
+    /// Recombines two networks of the same topology parameter by parameter,
+    /// taking each one from either parent with equal probability - same as
+    /// `UniformCrossover` from the genetic algorithm would do to
+    /// [`Self::weights()`].
+    pub fn crossover(&self, other: &Network, rng: &mut dyn RngCore) -> Network {
+        assert!(
+            self.topology() == other.topology(),
+            "networks have different topologies"
+        );
+
+        let mut child = self.clone();
+
+        for (weight, other) in child.weights_mut().zip(other.weights()) {
+            if !rng.gen_bool(0.5) {
+                *weight = other;
+            }
+        }
+
+        child
+    }
+
+    /// Like [`Self::crossover()`], but returns an error instead of panicking
+    /// when the networks have different topologies.
+    pub fn try_crossover(
+        &self,
+        other: &Network,
+        rng: &mut dyn RngCore,
+    ) -> Result<Network, NetworkError> {
+        if self.topology() != other.topology() {
+            return Err(NetworkError::TopologyMismatch);
+        }
+
+        Ok(self.crossover(other, rng))
+    }
+
+    /// Returns the root mean square difference between the two networks'
+    /// parameters (see [`Self::weights()`]), e.g. for speciation; 0 means
+    /// the networks are identical.
+    pub fn distance(&self, other: &Network) -> Float {
+        assert!(
+            self.topology() == other.topology(),
+            "networks have different topologies"
+        );
+
+        let sum: Float = self
+            .weights()
+            .zip(other.weights())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+
+        (sum / self.num_parameters().max(1) as Float).sqrt()
+    }
+
+    /// Like [`Self::distance()`], but returns an error instead of panicking
+    /// when the networks have different topologies.
+    pub fn try_distance(&self, other: &Network) -> Result<Float, NetworkError> {
+        if self.topology() != other.topology() {
+            return Err(NetworkError::TopologyMismatch);
+        }
+
+        Ok(self.distance(other))
+    }
+
+    /// Returns the sum of absolute values of this network's parameters (see
+    /// [`Self::weights()`]), e.g. to penalize complexity in fitness.
+    pub fn l1_norm(&self) -> Float {
+        Self::l1_norm_of(self.weights())
+    }
+
+    /// Returns the square root of the sum of squares of this network's
+    /// parameters (see [`Self::weights()`]).
+    pub fn l2_norm(&self) -> Float {
+        Self::l2_norm_of(self.weights())
+    }
+
+    /// Like [`Self::l1_norm()`], but only for given layer's parameters;
+    /// layers are counted like in [`Self::bias()`].
+    pub fn layer_l1_norm(&self, layer: usize) -> Option<Float> {
+        Some(Self::l1_norm_of(self.layers.get(layer)?.weights()))
+    }
+
+    /// Like [`Self::l2_norm()`], but only for given layer's parameters;
+    /// layers are counted like in [`Self::bias()`].
+    pub fn layer_l2_norm(&self, layer: usize) -> Option<Float> {
+        Some(Self::l2_norm_of(self.layers.get(layer)?.weights()))
+    }
+
+    fn l1_norm_of(weights: impl Iterator<Item = Float>) -> Float {
+        weights.map(Float::abs).sum()
+    }
+
+    fn l2_norm_of(weights: impl Iterator<Item = Float>) -> Float {
+        weights.map(|weight| weight * weight).sum::<Float>().sqrt()
+    }
+
+    /// Returns a hash of this network's topology (see [`Self::topology()`],
+    /// except for [`LayerTopology::init`]) and parameters, e.g. to spot
+    /// duplicate individuals or to cache fitness by brain.
+    ///
+    /// Parameters are rounded to multiples of 2^-16 first, so that networks
+    /// differing only by rounding errors get the same fingerprint. The hash
+    /// (FNV-1a) doesn't depend on the platform or the compiler, so
+    /// fingerprints can be stored.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+
+        let mut write = |value: u64| {
+            for byte in value.to_le_bytes() {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+            }
+        };
+
+        write(self.input_size() as u64);
+
+        for (layer, topology) in self.layers.iter().zip(&self.topology()[1..]) {
+            write(topology.neurons as u64);
+            write(layer.num_weights() as u64);
+
+            match topology.kind {
+                LayerKind::Dense => write(0),
+                LayerKind::Recurrent => write(1),
+                LayerKind::Lstm => write(2),
+                LayerKind::Gru => write(3),
+
+                LayerKind::Convolution(convolution) => {
+                    write(4);
+
+                    for value in [
+                        convolution.channels,
+                        convolution.width,
+                        convolution.kernel_width,
+                        convolution.kernel_height,
+                        convolution.filters,
+                    ] {
+                        write(value as u64);
+                    }
+                }
+
+                LayerKind::Plastic => write(5),
+                LayerKind::Custom => write(6),
+            }
+
+            match topology.activation {
+                Activation::Relu => write(0),
+
+                Activation::LeakyRelu(alpha) => {
+                    write(1);
+                    write(alpha.to_bits().into());
+                }
+
+                Activation::Prelu => write(2),
+                Activation::Sigmoid => write(3),
+                Activation::Tanh => write(4),
+                Activation::Linear => write(5),
+                Activation::Softmax => write(6),
+            }
+
+            write(u64::from(topology.bias) | u64::from(topology.normalize) << 1);
+            write(topology.dropout.to_bits().into());
+            write(topology.skip.map_or(u64::MAX, |skip| skip as u64));
+
+            match &topology.mask {
+                Some(mask) => {
+                    write(mask.len() as u64);
+
+                    for bits in mask.chunks(64) {
+                        write(
+                            bits.iter()
+                                .enumerate()
+                                .map(|(bit, &connected)| u64::from(connected) << bit)
+                                .sum(),
+                        );
+                    }
+                }
+
+                None => write(u64::MAX),
+            }
+        }
+
+        for weight in self.weights() {
+            write((weight * 65536.0).round() as i64 as u64);
+        }
+
+        hash
+    }
+
+    /// Returns the bias of given neuron; layers are counted from the first
+    /// layer that has weights (i.e. the input layer is skipped).
+    ///
+    /// Custom layers don't have neurons as far as the network is concerned,
+    /// so this returns `None` for them (and setters return
+    /// [`NetworkError::UnsupportedLayer`]).
+    pub fn bias(&self, layer: usize, neuron: usize) -> Option<Float> {
+        self.matrix(layer)?.bias(neuron)
+    }
+
+    pub fn set_bias(
+        &mut self,
+        layer: usize,
+        neuron: usize,
+        value: Float,
+    ) -> Result<(), NetworkError> {
+        *self
+            .matrix_mut(layer)?
+            .filter(|params| neuron < params.outputs())
+            .ok_or(NetworkError::NeuronOutOfBounds { layer, neuron })?
+            .bias_mut(neuron)
+            .ok_or(NetworkError::NoBias { layer })? = value;
+
+        Ok(())
+    }
+
+    /// Returns the weight connecting given neuron to its `index`-th input;
+    /// layers are counted like in [`Self::bias()`].
+    ///
+    /// Neurons of recurrent layers have their input weights followed by
+    /// weights for the layer's previous outputs.
+    pub fn weight(&self, layer: usize, neuron: usize, index: usize) -> Option<Float> {
+        self.matrix(layer)?.weight(neuron, index)
+    }
+
+    pub fn set_weight(
+        &mut self,
+        layer: usize,
+        neuron: usize,
+        index: usize,
+        value: Float,
+    ) -> Result<(), NetworkError> {
+        let params = self
+            .matrix_mut(layer)?
+            .filter(|params| neuron < params.outputs())
+            .ok_or(NetworkError::NeuronOutOfBounds { layer, neuron })?;
+
+        if index < params.inputs() && !params.is_connected(neuron, index) {
+            return Err(NetworkError::MaskedWeight {
+                layer,
+                neuron,
+                index,
+            });
+        }
+
+        let weight = params
+            .weight_mut(neuron, index)
+            .ok_or(NetworkError::WeightOutOfBounds {
+                layer,
+                neuron,
+                index,
+            })?;
+
+        *weight = value;
+
+        Ok(())
+    }
+
+    fn matrix(&self, layer: usize) -> Option<&DenseLayer> {
+        self.layers
+            .get(layer)
+            .filter(|layer| !layer.is_custom())
+            .map(Layer::params)
+    }
+
+    /// Returns the matrix of given layer, if the layer exists, or an error if
+    /// it's a custom one.
+    fn matrix_mut(&mut self, layer: usize) -> Result<Option<&mut DenseLayer>, NetworkError> {
+        match self.layers.get_mut(layer) {
+            Some(Layer::Custom(_)) => Err(NetworkError::UnsupportedLayer { layer }),
+            layer => Ok(layer.map(Layer::params_mut)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    mod network {
+        use super::*;
+
+        mod random {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            #[test]
+            fn test() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let network = Network::random(
+                    &mut rng,
+                    &[
+                        LayerTopology {
+                            neurons: 3,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 2,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 1,
+                            ..Default::default()
+                        },
+                    ],
+                );
+
+                assert_eq!(network.layers.len(), 2);
+                assert_eq!(network.layers[0].outputs(), 2);
+
+                approx::assert_relative_eq!(
+                    network.layers[0].params().row(0),
+                    &[0.67383957, 0.8181262, 0.26284897].as_slice(),
+                    max_relative = TOLERANCE
+                );
+
+                approx::assert_relative_eq!(
+                    network.layers[0].params().row(1),
+                    &[-0.53516835, 0.069369674, -0.7648182].as_slice(),
+                    max_relative = TOLERANCE
+                );
+
+                assert_eq!(network.layers[1].outputs(), 1);
+
+                approx::assert_relative_eq!(
+                    network.layers[1].params().row(0),
+                    &[-0.48879617, -0.19277132].as_slice(),
+                    max_relative = TOLERANCE
+                );
+            }
+        }
+
+        mod propagate {
+            use super::*;
+
+            #[test]
+            fn test() {
+                let layers = vec![
+                    Layer::Dense(DenseLayer::new(
+                        vec![0.1, 0.5],
+                        vec![vec![0.2, 0.3, 0.4], vec![0.6, 0.7, 0.8]],
+                        Activation::Relu,
+                    )),
+                    Layer::Dense(DenseLayer::new(
+                        vec![0.2],
+                        vec![vec![-0.5, 0.5]],
+                        Activation::Sigmoid,
+                    )),
+                ];
+
+                let network = Network::new(layers.clone());
+
+                let actual = network.propagate(vec![0.5, 0.6, 0.7]);
+                let expected = layers[1].propagate(&layers[0].propagate(&[0.5, 0.6, 0.7]));
+
+                assert_relative_eq!(actual.as_slice(), expected.as_slice());
+            }
+
+            #[test]
+            fn from_many_threads() {
+                fn assert_send_sync<T: Send + Sync>() {}
+
+                assert_send_sync::<Network>();
+                assert_send_sync::<NetworkState>();
+            }
+        }
+
+        mod reset_state {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            #[test]
+            fn test() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let network = Network::random(
+                    &mut rng,
+                    &[
+                        LayerTopology {
+                            neurons: 2,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 3,
+                            activation: Activation::Tanh,
+                            kind: LayerKind::Recurrent,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 1,
+                            activation: Activation::Linear,
+                            ..Default::default()
+                        },
+                    ],
+                );
+
+                let mut state = network.state();
+                let first = network.propagate_with(&mut state, vec![0.5, -0.5]);
+                let second = network.propagate_with(&mut state, vec![0.5, -0.5]);
+
+                assert!((first[0] - second[0]).abs() > 1e-3);
+
+                // Without a state, each call starts from a blank one
+                assert_eq!(network.propagate(vec![0.5, -0.5]), first);
+
+                network.reset_state(&mut state);
+
+                let third = network.propagate_with(&mut state, vec![0.5, -0.5]);
+
+                assert_relative_eq!(third.as_slice(), first.as_slice());
+            }
+
+            #[test]
+            #[should_panic]
+            fn panics_on_foreign_state() {
+                let network = NetworkBuilder::new(1)
+                    .layer(1)
+                    .kind(LayerKind::Recurrent)
+                    .build_from_weights([0.0; 3]);
+
+                let other = NetworkBuilder::new(1)
+                    .layer(1)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.0; 4]);
+
+                network.propagate_with(&mut other.state(), vec![1.0]);
+            }
+
+            #[test]
+            fn plastic() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let network = NetworkBuilder::new(2)
+                    .layer(3)
+                    .kind(LayerKind::Plastic)
+                    .output(1, Activation::Linear)
+                    .build_with(&mut rng, rand_distr::Uniform::new(0.5, 1.0));
+
+                let weights: Vec<_> = network.weights().collect();
+                let mut state = network.state();
+                let first = network.propagate_with(&mut state, vec![0.5, 0.5]);
+                let second = network.propagate_with(&mut state, vec![0.5, 0.5]);
+
+                assert!(second[0] > first[0] + 1e-3);
+                assert_eq!(network.weights().collect::<Vec<_>>(), weights);
+
+                network.reset_state(&mut state);
+
+                let third = network.propagate_with(&mut state, vec![0.5, 0.5]);
+
+                assert_relative_eq!(third.as_slice(), first.as_slice());
+            }
+        }
+
+        mod propagate_in_place {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            fn network() -> Network {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                Network::random(
+                    &mut rng,
+                    &[
+                        LayerTopology {
+                            neurons: 3,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 5,
+                            activation: Activation::Tanh,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 2,
+                            activation: Activation::Sigmoid,
+                            ..Default::default()
+                        },
+                    ],
+                )
+            }
+
+            #[test]
+            fn matches_propagate() {
+                let network = network();
+                let mut buffer = PropagateBuffer::new();
+
+                for inputs in [[0.5, 0.6, 0.7], [-1.0, 0.0, 1.0]] {
+                    let expected = network.propagate(inputs.to_vec());
+                    let actual = network.propagate_in_place(&inputs, &mut buffer);
+
+                    assert_relative_eq!(actual, expected.as_slice());
+                }
+            }
+
+            #[test]
+            fn reuses_buffer() {
+                let network = network();
+                let mut buffer = PropagateBuffer::new();
+
+                let allocations = |buffer: &PropagateBuffer| {
+                    let mut ptrs = [buffer.front.as_ptr(), buffer.back.as_ptr()];
+                    ptrs.sort();
+                    ptrs
+                };
+
+                // The first two calls grow both halves of the buffer to the
+                // widest layer; nothing should get reallocated afterwards
+                network.propagate_in_place(&[0.5, 0.6, 0.7], &mut buffer);
+                network.propagate_in_place(&[0.5, 0.6, 0.7], &mut buffer);
+
+                let expected = allocations(&buffer);
+
+                for _ in 0..5 {
+                    network.propagate_in_place(&[0.1, 0.2, 0.3], &mut buffer);
+                }
+
+                assert_eq!(allocations(&buffer), expected);
+            }
+
+            fn recurrent() -> Network {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                NetworkBuilder::new(1)
+                    .layer(2)
+                    .kind(LayerKind::Recurrent)
+                    .activation(Activation::Tanh)
+                    .output(1, Activation::Linear)
+                    .build(&mut rng)
+            }
+
+            #[test]
+            fn keeps_state_in_buffer() {
+                let network = recurrent();
+                let mut state = network.state();
+                let mut buffer = PropagateBuffer::new();
+
+                for inputs in [[1.0], [0.0], [0.0]] {
+                    let expected = network.propagate_with(&mut state, inputs.to_vec());
+                    let actual = network.propagate_in_place(&inputs, &mut buffer);
+
+                    assert_eq!(actual, expected.as_slice());
+                }
+
+                buffer.reset_state();
+
+                assert_eq!(
+                    network.propagate_in_place(&[0.0], &mut buffer),
+                    network.propagate(vec![0.0]).as_slice()
+                );
+            }
+
+            #[test]
+            fn with_state() {
+                let network = recurrent();
+                let mut expected_state = network.state();
+                let mut state = network.state();
+                let mut buffer = PropagateBuffer::new();
+
+                for inputs in [[1.0], [0.0], [0.0]] {
+                    let expected = network.propagate_with(&mut expected_state, inputs.to_vec());
+                    let actual = network.propagate_in_place_with(&mut state, &inputs, &mut buffer);
+
+                    assert_eq!(actual, expected.as_slice());
+                }
+
+                assert_eq!(state, expected_state);
+                assert_eq!(buffer.state, None);
+            }
+        }
+
+        mod propagate_trace {
+            use super::*;
+
+            #[test]
+            fn test() {
+                let mut network = NetworkBuilder::new(2)
+                    .layer(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.0, 1.0, 0.0, 0.0, 0.0, -1.0, 0.5, 1.0, 1.0]);
+
+                network.set_normalizer(Some(Normalizer::new(vec![0.0; 2], vec![2.0; 2])));
+
+                let (outputs, trace) = network.propagate_trace(vec![1.0, 2.0]);
+
+                assert_eq!(outputs, network.propagate(vec![1.0, 2.0]));
+                assert_eq!(trace.len(), 2);
+                assert_eq!(trace.inputs(), [0.5, 1.0]);
+                assert_eq!(trace.layer(0), Some([0.5, 0.0].as_ref()));
+                assert_eq!(trace.layer(1), Some(outputs.as_slice()));
+                assert_eq!(trace.layer(2), None);
+                assert_eq!(trace.layers().count(), 2);
+            }
+        }
+
+        mod propagate_into {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            fn network() -> Network {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                NetworkBuilder::new(3)
+                    .layer(5)
+                    .activation(Activation::Tanh)
+                    .output(2, Activation::Sigmoid)
+                    .build(&mut rng)
+            }
+
+            #[test]
+            fn test() {
+                let network = network();
+                let mut outputs = [0.0; 2];
+                let mut buffer = PropagateBuffer::new();
+
+                for inputs in [[0.5, 0.6, 0.7], [-1.0, 0.0, 1.0]] {
+                    network.propagate_into(&inputs, &mut outputs, &mut buffer);
+
+                    assert_eq!(outputs.to_vec(), network.propagate(inputs.to_vec()));
+                }
+            }
+
+            #[test]
+            fn with_state() {
+                let network = network();
+                let mut state = network.state();
+                let mut outputs = [0.0; 2];
+                let mut buffer = PropagateBuffer::new();
+
+                network.propagate_into_with(
+                    &mut state,
+                    &[0.5, 0.6, 0.7],
+                    &mut outputs,
+                    &mut buffer,
+                );
+
+                assert_eq!(outputs.to_vec(), network.propagate(vec![0.5, 0.6, 0.7]));
+            }
+
+            #[test]
+            #[should_panic]
+            fn panics_on_wrong_output_size() {
+                network().propagate_into(
+                    &[0.5, 0.6, 0.7],
+                    &mut [0.0; 3],
+                    &mut PropagateBuffer::new(),
+                );
+            }
+        }
+
+        mod propagate_batch {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            #[test]
+            fn test() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let network = Network::random(
+                    &mut rng,
+                    &[
+                        LayerTopology {
+                            neurons: 3,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 4,
+                            activation: Activation::Tanh,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 2,
+                            activation: Activation::Linear,
+                            ..Default::default()
+                        },
+                    ],
+                );
+
+                let inputs = vec![
+                    vec![0.5, 0.6, 0.7],
+                    vec![-1.0, 0.0, 1.0],
+                    vec![0.0, 0.0, 0.0],
+                ];
+
+                let actual = network.propagate_batch(&inputs);
+
+                assert_eq!(actual.len(), inputs.len());
+
+                for (actual, inputs) in actual.iter().zip(inputs) {
+                    let expected = network.propagate(inputs);
+
+                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                }
+            }
+
+            #[test]
+            fn empty_batch() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let network = Network::random(
+                    &mut rng,
+                    &[
+                        LayerTopology {
+                            neurons: 3,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 1,
+                            ..Default::default()
+                        },
+                    ],
+                );
+
+                assert!(network.propagate_batch(&[]).is_empty());
+            }
+        }
+
+        mod weights {
+            use super::*;
+
+            #[test]
+            fn test() {
+                let network = Network::new(vec![
+                    Layer::Dense(DenseLayer::new(
+                        vec![0.1],
+                        vec![vec![0.2, 0.3, 0.4]],
+                        Activation::Relu,
+                    )),
+                    Layer::Dense(DenseLayer::new(
+                        vec![0.5],
+                        vec![vec![0.6]],
+                        Activation::Relu,
+                    )),
+                ]);
+
+                let actual: Vec<_> = network.weights().collect();
+                let expected = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+
+                assert_relative_eq!(actual.as_slice(), expected.as_slice());
+            }
+        }
+
+        mod weights_mut {
+            use super::*;
+
+            #[test]
+            fn test() {
+                let weights: Vec<_> = (0..8).map(|w| w as Float).collect();
+
+                let mut network = NetworkBuilder::new(2)
+                    .layer(2)
+                    .bias(false)
+                    .output(1, Activation::Prelu)
+                    .build_from_weights(weights.clone());
+
+                for (id, weight) in network.weights_mut().enumerate() {
+                    assert_eq!(*weight, weights[id]);
+                    *weight = -*weight;
+                }
+
+                assert_eq!(
+                    network.weights().collect::<Vec<_>>(),
+                    weights.iter().map(|w| -w).collect::<Vec<_>>()
+                );
+            }
+        }
+
+        mod topology {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            #[test]
+            fn test() {
+                let topology = NetworkBuilder::new(3)
+                    .layer(4)
+                    .activation(Activation::Tanh)
+                    .kind(LayerKind::Recurrent)
+                    .layer(2)
+                    .kind(LayerKind::Lstm)
+                    .output(1, Activation::Sigmoid)
+                    .topology()
+                    .to_vec();
+
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let network = Network::random(&mut rng, &topology);
+
+                assert_eq!(network.topology(), topology);
+                assert_eq!(network.input_size(), 3);
+                assert_eq!(network.output_size(), 1);
+
+                assert_eq!(network.num_parameters(), network.weights().count());
+                assert_eq!(network.num_parameters(), 4 * 8 + 4 * 2 * 7 + 3);
+            }
+        }
+
+        mod random_with {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            fn layers() -> Vec<LayerTopology> {
+                NetworkBuilder::new(3)
+                    .layer(2)
+                    .kind(LayerKind::Recurrent)
+                    .output(1, Activation::Linear)
+                    .topology()
+                    .to_vec()
+            }
+
+            #[test]
+            fn test() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let distribution = rand_distr::Uniform::new(2.0, 3.0);
+                let network = Network::random_with(&mut rng, &layers(), distribution);
+
+                assert_eq!(network.topology(), layers());
+                assert_eq!(network.weights().count(), 2 * (1 + 3 + 2) + (1 + 2));
+                assert!(network.weights().all(|w| (2.0..3.0).contains(&w)));
+            }
+
+            #[test]
+            fn matches_from_weights() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let network = Network::random_with(&mut rng, &layers(), rand_distr::StandardNormal);
+
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let weights: Vec<Float> = (0..network.num_parameters())
+                    .map(|_| Float::from(rng.sample::<f32, _>(rand_distr::StandardNormal)))
+                    .collect();
+
+                assert!(network.weights().eq(weights));
+            }
+        }
+
+        mod from_weights {
+            use super::*;
+
+            fn layers() -> [LayerTopology; 2] {
+                [
+                    LayerTopology {
+                        neurons: 3,
+                        ..Default::default()
+                    },
+                    LayerTopology {
+                        neurons: 2,
+                        ..Default::default()
+                    },
+                ]
+            }
+
+            #[test]
+            fn test() {
+                let weights = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+                let network = Network::from_weights(&layers(), weights.clone());
+                let actual: Vec<_> = network.weights().collect();
+
+                assert_relative_eq!(actual.as_slice(), weights.as_slice());
+            }
+
+            #[test]
+            #[should_panic(expected = "got not enough weights")]
+            fn panics_on_not_enough_weights() {
+                Network::from_weights(&layers(), vec![0.1, 0.2, 0.3]);
+            }
+
+            #[test]
+            #[should_panic(expected = "got too many weights")]
+            fn panics_on_too_many_weights() {
+                Network::from_weights(&layers(), vec![0.5; 9]);
+            }
+        }
+
+        mod from_layers {
+            use super::*;
+
+            fn layer(biases: Vec<Float>, weights: Vec<Vec<Float>>) -> LayerWeights {
+                LayerWeights {
+                    biases,
+                    weights,
+                    activation: Activation::Linear,
+                }
+            }
+
+            #[test]
+            fn test() {
+                let network = Network::from_layers(vec![
+                    layer(
+                        vec![0.1, 0.2],
+                        vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]],
+                    ),
+                    layer(vec![0.3], vec![vec![-1.0, 1.0]]),
+                ]);
+
+                let expected = NetworkBuilder::new(3)
+                    .layer(2)
+                    .activation(Activation::Linear)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.1, 1.0, 2.0, 3.0, 0.2, 4.0, 5.0, 6.0, 0.3, -1.0, 1.0]);
+
+                assert_eq!(network, expected);
+            }
+
+            #[test]
+            fn rejects_no_layers() {
+                assert_eq!(
+                    Network::try_from_layers(Vec::new()).unwrap_err(),
+                    NetworkError::NotEnoughLayers { actual: 0 }
+                );
+            }
+
+            #[test]
+            fn rejects_invalid_layers() {
+                assert_eq!(
+                    Network::try_from_layers(vec![layer(vec![0.0], vec![vec![1.0], vec![2.0]])])
+                        .unwrap_err(),
+                    NetworkError::InvalidLayerWeights { layer: 0 }
+                );
+
+                assert_eq!(
+                    Network::try_from_layers(vec![
+                        layer(vec![0.0], vec![vec![1.0]]),
+                        layer(vec![0.0, 0.0], vec![vec![1.0], vec![2.0, 3.0]]),
+                    ])
+                    .unwrap_err(),
+                    NetworkError::InvalidLayerWeights { layer: 1 }
+                );
+
+                assert_eq!(
+                    Network::try_from_layers(vec![
+                        layer(vec![0.0], vec![vec![1.0]]),
+                        layer(vec![0.0], vec![vec![1.0, 2.0]]),
+                    ])
+                    .unwrap_err(),
+                    NetworkError::InputSizeMismatch {
+                        expected: 1,
+                        actual: 2,
+                    }
+                );
+            }
+        }
+
+        mod try_from_weights {
+            use super::*;
+
+            fn layers() -> Vec<LayerTopology> {
+                NetworkBuilder::new(2)
+                    .layer(2)
+                    .kind(LayerKind::Gru)
+                    .output(1, Activation::Linear)
+                    .topology()
+                    .to_vec()
+            }
+
+            #[test]
+            fn accepts_matching_weights() {
+                // GRU: 3 gates × 2 neurons × (bias + 2 inputs + 2 recurrent),
+                // then the output neuron: bias + 2 inputs
+                let network = Network::try_from_weights(&layers(), vec![0.5; 30 + 3]);
+
+                assert!(network.is_ok());
+            }
+
+            #[test]
+            fn rejects_single_layer() {
+                let network = Network::try_from_weights(&layers()[..1], Vec::new());
+
+                assert_eq!(
+                    network.err(),
+                    Some(NetworkError::NotEnoughLayers { actual: 1 })
+                );
+            }
+
+            #[test]
+            fn rejects_wrong_weight_count() {
+                for actual in [32, 34] {
+                    let network = Network::try_from_weights(&layers(), vec![0.5; actual]);
+
+                    assert_eq!(
+                        network.err(),
+                        Some(NetworkError::WeightCountMismatch {
+                            expected: 33,
+                            actual
+                        })
+                    );
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        mod serde {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            #[test]
+            fn roundtrip() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let network = Network::random(
+                    &mut rng,
+                    &[
+                        LayerTopology {
+                            neurons: 3,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 2,
+                            activation: Activation::Tanh,
+                            ..Default::default()
+                        },
+                    ],
+                );
+
+                let json = serde_json::to_string(&network).unwrap();
+                let actual: Network = serde_json::from_str(&json).unwrap();
+
+                let actual_weights: Vec<_> = actual.weights().collect();
+                let expected_weights: Vec<_> = network.weights().collect();
+
+                assert_relative_eq!(actual_weights.as_slice(), expected_weights.as_slice());
+
+                let actual_outputs = actual.propagate(vec![0.5, 0.6, 0.7]);
+                let expected_outputs = network.propagate(vec![0.5, 0.6, 0.7]);
+
+                assert_relative_eq!(actual_outputs.as_slice(), expected_outputs.as_slice());
+            }
+        }
+
+        mod try_random {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            #[test]
+            fn rejects_single_layer() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let network = Network::try_random(
+                    &mut rng,
+                    &[LayerTopology {
+                        neurons: 3,
+                        ..Default::default()
+                    }],
+                );
+
+                assert_eq!(
+                    network.err(),
+                    Some(NetworkError::NotEnoughLayers { actual: 1 })
+                );
+            }
+
+            #[test]
+            fn rejects_invalid_convolution() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let convolution = Convolution {
+                    channels: 1,
+                    width: 4,
+                    kernel_width: 2,
+                    kernel_height: 1,
+                    filters: 2,
+                };
+
+                let network = Network::try_random(
+                    &mut rng,
+                    &[
+                        LayerTopology {
+                            neurons: 4,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 4,
+                            kind: LayerKind::Convolution(convolution),
+                            ..Default::default()
+                        },
+                    ],
+                );
+
+                assert_eq!(
+                    network.err(),
+                    Some(NetworkError::InvalidConvolution { layer: 0 })
+                );
+            }
+
+            #[test]
+            fn rejects_invalid_skip() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let skips = [
+                    // Different number of neurons
+                    NetworkBuilder::new(3).layer(2).skip(0),
+                    // Not an earlier layer
+                    NetworkBuilder::new(2).layer(2).skip(1),
+                    // Not a dense layer
+                    NetworkBuilder::new(2)
+                        .layer(2)
+                        .kind(LayerKind::Recurrent)
+                        .skip(0),
+                ];
+
+                for builder in skips {
+                    assert_eq!(
+                        Network::try_random(&mut rng, builder.topology()).err(),
+                        Some(NetworkError::InvalidSkip { layer: 0 })
+                    );
+                }
+            }
+
+            #[test]
+            fn accepts_two_layers() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let network = Network::try_random(
+                    &mut rng,
+                    &[
+                        LayerTopology {
+                            neurons: 3,
+                            ..Default::default()
+                        },
+                        LayerTopology {
+                            neurons: 1,
+                            ..Default::default()
+                        },
+                    ],
+                );
+
+                assert!(network.is_ok());
+            }
+        }
+
+        mod skip {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            fn builder() -> NetworkBuilder {
+                NetworkBuilder::new(2)
+                    .layer(3)
+                    .activation(Activation::Tanh)
+                    .layer(2)
+                    .skip(0)
+                    .layer(3)
+                    .skip(1)
+                    .output(2, Activation::Linear)
+                    .skip(2)
+            }
+
+            #[test]
+            fn adds_outputs_before_activation() {
+                let network = NetworkBuilder::new(2)
+                    .layer(2)
+                    .skip(0)
+                    .build_from_weights([0.5, 1.0, 0.0, 0.0, 0.0, 1.0]);
+
+                // relu([0.5 + 2.0 + 2.0, 0.0 - 3.0 - 3.0])
+                assert_eq!(network.propagate(vec![2.0, -3.0]), [4.5, 0.0]);
+            }
+
+            #[test]
+            fn topology() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let network = builder().build(&mut rng);
+
+                assert_eq!(network.topology(), builder().topology());
+            }
+
+            #[test]
+            fn propagations_agree() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let network = builder().build(&mut rng);
+                let inputs = vec![vec![0.5, -1.0], vec![1.0, 2.0]];
+
+                let expected: Vec<_> = inputs
+                    .iter()
+                    .map(|inputs| network.propagate(inputs.clone()))
+                    .collect();
+
+                let mut buffer = PropagateBuffer::new();
+
+                for (inputs, expected) in inputs.iter().zip(&expected) {
+                    assert_eq!(network.propagate_in_place(inputs, &mut buffer), expected);
+                }
+
+                assert_eq!(network.propagate_batch(&inputs), expected);
+            }
+
+            #[test]
+            #[should_panic]
+            fn panics_on_invalid_skip() {
+                NetworkBuilder::new(3)
+                    .layer(2)
+                    .skip(0)
+                    .build_from_weights([0.0; 8]);
+            }
+        }
+
+        mod bias {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            #[test]
+            fn test() {
+                let network = NetworkBuilder::new(2)
+                    .layer(2)
+                    .bias(false)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([1.0, 2.0, 3.0, 4.0, 0.5, 1.0, 1.0]);
+
+                assert_eq!(network.num_parameters(), 2 * 2 + 3);
+                assert_eq!(network.bias(0, 0), Some(0.0));
+                assert_eq!(network.weight(0, 1, 0), Some(3.0));
+
+                // 0.5 + relu(1 + 2) + relu(3 + 4)
+                assert_eq!(network.propagate(vec![1.0, 1.0]), [10.5]);
+            }
+
+            #[test]
+            fn all_kinds() {
+                let convolution = Convolution {
+                    channels: 1,
+                    width: 4,
+                    kernel_width: 2,
+                    kernel_height: 1,
+                    filters: 2,
+                };
+
+                let builder = NetworkBuilder::new(4)
+                    .convolution(convolution)
+                    .bias(false)
+                    .layer(3)
+                    .kind(LayerKind::Recurrent)
+                    .bias(false)
+                    .layer(3)
+                    .kind(LayerKind::Lstm)
+                    .bias(false)
+                    .layer(2)
+                    .kind(LayerKind::Gru)
+                    .bias(false);
+
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let network = builder.build(&mut rng);
+
+                assert_eq!(network.topology(), builder.topology());
+
+                assert_eq!(
+                    network.num_parameters(),
+                    2 * 2 + 3 * (6 + 3) + 4 * 3 * (3 + 3) + 3 * 2 * (3 + 2)
+                );
+
+                assert!(Network::try_from_weights(builder.topology(), network.weights()).is_ok());
+            }
+
+            #[test]
+            fn mutate_keeps_biases_zero() {
+                let mut network = NetworkBuilder::new(2)
+                    .layer(3)
+                    .bias(false)
+                    .build_from_weights([0.0; 6]);
+
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                network.mutate(&mut rng, 1.0, 1.0);
+
+                assert!((0..3).all(|neuron| network.bias(0, neuron) == Some(0.0)));
+                assert!(network.weights().all(|weight| weight != 0.0));
+            }
+
+            #[test]
+            fn set_bias() {
+                let mut network = NetworkBuilder::new(2)
+                    .layer(3)
+                    .bias(false)
+                    .build_from_weights([0.0; 6]);
+
+                assert_eq!(
+                    network.set_bias(0, 1, 1.0),
+                    Err(NetworkError::NoBias { layer: 0 })
+                );
+
+                assert_eq!(
+                    network.set_bias(0, 3, 1.0),
+                    Err(NetworkError::NeuronOutOfBounds {
+                        layer: 0,
+                        neuron: 3
+                    })
+                );
+            }
+        }
+
+        mod mask {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            fn builder() -> NetworkBuilder {
+                NetworkBuilder::new(3)
+                    .output(2, Activation::Linear)
+                    .mask(vec![true, false, true, false, true, false])
+            }
+
+            #[test]
+            fn test() {
+                let network = builder().build_from_weights([0.5, 1.0, 2.0, -0.5, 3.0]);
+
+                assert_eq!(network.num_parameters(), 5);
+                assert_eq!(network.weight(0, 0, 1), Some(0.0));
+                assert_eq!(network.weight(0, 1, 1), Some(3.0));
+
+                let actual = network.propagate(vec![1.0, 2.0, 3.0]);
+
+                assert_relative_eq!(actual.as_slice(), [7.5, 5.5].as_ref());
+                assert_eq!(
+                    Network::from_weights(&network.topology(), network.weights()),
+                    network
+                );
+            }
+
+            #[test]
+            fn survives_random_and_mutate() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut network = builder().build(&mut rng);
+
+                network.mutate(&mut rng, 1.0, 1.0);
+
+                for (neuron, index) in [(0, 1), (1, 0), (1, 2)] {
+                    assert_eq!(network.weight(0, neuron, index), Some(0.0));
+                }
+
+                assert_ne!(network.weight(0, 0, 0), Some(0.0));
+            }
+
+            #[test]
+            fn rejects_masked_weights() {
+                let mut network = builder().build_from_weights([0.0; 5]);
+
+                assert_eq!(
+                    network.set_weight(0, 1, 0, 1.0),
+                    Err(NetworkError::MaskedWeight {
+                        layer: 0,
+                        neuron: 1,
+                        index: 0,
+                    })
+                );
+
+                assert_eq!(network.set_weight(0, 1, 1, 1.0), Ok(()));
+            }
+
+            #[test]
+            fn rejects_invalid_masks() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let builder = NetworkBuilder::new(3)
+                    .output(2, Activation::Linear)
+                    .mask(vec![true; 5]);
+
+                assert_eq!(
+                    Network::try_random(&mut rng, builder.topology()).err(),
+                    Some(NetworkError::InvalidMask { layer: 0 })
+                );
+
+                let builder = NetworkBuilder::new(1)
+                    .output(1, Activation::Linear)
+                    .kind(LayerKind::Recurrent)
+                    .mask(vec![true]);
+
+                assert_eq!(
+                    Network::try_random(&mut rng, builder.topology()).err(),
+                    Some(NetworkError::InvalidMask { layer: 0 })
+                );
+            }
+        }
+
+        mod normalize {
+            use super::*;
+
+            fn network(scale: Float) -> Network {
+                let weights: Vec<_> = (0..3 * 3).map(|w| scale * (w as Float - 4.0)).collect();
+
+                NetworkBuilder::new(2)
+                    .layer(3)
+                    .activation(Activation::Tanh)
+                    .normalize(true)
+                    .build_from_weights(weights)
+            }
+
+            #[test]
+            fn ignores_scale_of_weights() {
+                let expected = network(1.0).propagate(vec![0.5, -1.0]);
+                let actual = network(1000.0).propagate(vec![0.5, -1.0]);
+
+                assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 1e-4);
+            }
+
+            #[test]
+            fn topology() {
+                let network = network(1.0);
+
+                let topology = network.topology();
+
+                assert!(topology[1].normalize);
+                assert_eq!(Network::from_weights(&topology, network.weights()), network);
+            }
+
+            #[test]
+            fn quantize() {
+                let network = network(1.0);
+
+                let expected = network.propagate(vec![0.5, -1.0]);
+                let actual = network.quantize().propagate(vec![0.5, -1.0]);
+
+                assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 0.02);
+            }
+        }
+
+        mod normalizer {
+            use super::*;
+
+            fn network() -> Network {
+                let mut network = NetworkBuilder::new(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.0, 1.0, 2.0]);
+
+                network.set_normalizer(Some(Normalizer::new(vec![0.0, 10.0], vec![2.0, 20.0])));
+                network
+            }
+
+            #[test]
+            fn test() {
+                let actual = network().propagate(vec![1.0, 15.0]);
+
+                assert_relative_eq!(actual.as_slice(), [1.5].as_ref());
+            }
+
+            #[test]
+            fn in_place_and_batch() {
+                let network = network();
+                let mut buffer = PropagateBuffer::new();
+
+                assert_relative_eq!(
+                    network.propagate_in_place(&[1.0, 15.0], &mut buffer),
+                    [1.5].as_ref()
+                );
+
+                assert_eq!(
+                    network.propagate_batch(&[vec![1.0, 15.0], vec![2.0, 30.0]]),
+                    [vec![1.5], vec![3.0]]
+                );
+            }
+
+            #[test]
+            fn quantize() {
+                let actual = network().quantize().propagate(vec![1.0, 15.0]);
+
+                assert_relative_eq!(actual.as_slice(), [1.5].as_ref(), epsilon = 0.02);
+            }
+
+            #[test]
+            #[should_panic]
+            fn panics_on_wrong_size() {
+                network().set_normalizer(Some(Normalizer::new(vec![0.0], vec![1.0])));
+            }
+        }
+
+        mod input_noise {
+            use super::*;
+            use rand_chacha::ChaCha8Rng;
+
+            fn network() -> Network {
+                NetworkBuilder::new(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.0, 1.0, 2.0])
+            }
+
+            fn noisy(sigma: f32) -> Network {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut network = network();
+
+                network.enable_input_noise(sigma, &mut rng);
+                network
+            }
+
+            #[test]
+            fn test() {
+                let network = noisy(0.5);
+
+                assert_eq!(network.input_noise(), Some(0.5));
+
+                let mut state = network.state();
+                let first = network.propagate_with(&mut state, vec![1.0, 1.0]);
+                let second = network.propagate_with(&mut state, vec![1.0, 1.0]);
+
+                assert_ne!(first, [3.0]);
+                assert_ne!(first, second);
+            }
+
+            #[test]
+            fn in_place() {
+                let network = noisy(0.5);
+                let mut buffer = PropagateBuffer::new();
+
+                let first = network
+                    .propagate_in_place(&[1.0, 1.0], &mut buffer)
+                    .to_vec();
+                let second = network
+                    .propagate_in_place(&[1.0, 1.0], &mut buffer)
+                    .to_vec();
+
+                assert_ne!(first, [3.0]);
+                assert_ne!(first, second);
+            }
+
+            #[test]
+            fn skipped_without_state() {
+                let network = noisy(0.5);
+
+                assert_eq!(network.propagate(vec![1.0, 1.0]), [3.0]);
+                assert_eq!(network.propagate_batch(&[vec![1.0, 1.0]]), [[3.0]]);
+            }
+
+            #[test]
+            fn reproducible() {
+                let (a, b) = (noisy(0.5), noisy(0.5));
+                let (mut state_a, mut state_b) = (a.state(), b.state());
+
+                for _ in 0..3 {
+                    assert_eq!(
+                        a.propagate_with(&mut state_a, vec![1.0, 1.0]),
+                        b.propagate_with(&mut state_b, vec![1.0, 1.0])
+                    );
+                }
+            }
+
+            #[test]
+            fn zero_sigma() {
+                let network = noisy(0.0);
+                let actual = network.propagate_with(&mut network.state(), vec![1.0, 1.0]);
+
+                assert_relative_eq!(actual.as_slice(), [3.0].as_ref());
+            }
+
+            #[test]
+            fn disabled() {
+                let mut network = noisy(0.5);
+
+                network.disable_input_noise();
+
+                let actual = network.propagate_with(&mut network.state(), vec![1.0, 1.0]);
+
+                assert_eq!(network.input_noise(), None);
+                assert_relative_eq!(actual.as_slice(), [3.0].as_ref());
+            }
+
+            #[test]
+            #[should_panic]
+            fn panics_on_negative_sigma() {
+                noisy(-1.0);
+            }
+        }
+
+        mod prelu {
+            use super::*;
+            use rand::SeedableRng;
+            use rand_chacha::ChaCha8Rng;
+
+            fn builder() -> NetworkBuilder {
+                NetworkBuilder::new(1)
+                    .layer(1)
+                    .activation(Activation::Prelu)
+            }
+
+            #[test]
+            fn alpha_is_part_of_the_genome() {
+                let network = builder().build_from_weights([0.0, 1.0, 0.5]);
+
+                assert_eq!(network.num_parameters(), 3);
+                assert_eq!(network.propagate(vec![-2.0]), [-1.0]);
+                assert_eq!(network.propagate(vec![2.0]), [2.0]);
+            }
+
+            #[test]
+            fn random() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let network = builder().build(&mut rng);
+
+                assert_eq!(
+                    network.weights().last(),
+                    Some(Float::from(Activation::PRELU_ALPHA))
+                );
+
+                assert_eq!(network.topology(), builder().topology());
+            }
+
+            #[test]
+            fn mutate() {
+                let mut network = builder().build_from_weights([0.0, 1.0, 0.5]);
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                network.mutate(&mut rng, 1.0, 0.1);
+
+                assert_ne!(network.weights().last(), Some(0.5));
+            }
+        }
+
+        mod dropout {
+            use super::*;
+
+            fn network() -> Network {
+                NetworkBuilder::new(1)
+                    .layer(1000)
+                    .dropout(0.25)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([1.0; 2000 + 1001])
+            }
+
+            #[test]
+            fn inactive_outside_training_mode() {
+                let network = network();
+
+                assert!(!network.is_training());
+                assert_eq!(network.propagate(vec![1.0]), [2001.0]);
+            }
+
+            #[test]
+            fn active_in_training_mode() {
+                let mut network = network();
+
+                network.enable_training(0);
+
+                let outputs = network.propagate_with(&mut network.state(), vec![1.0]);
+                let mut buffer = PropagateBuffer::new();
+                let in_place = network.propagate_in_place(&[1.0], &mut buffer)[0];
+
+                // Each hidden neuron outputs 2.0, scaled up to 2.0 / 0.75 when
+                // it isn't dropped
+                for output in [outputs[0], in_place] {
+                    let kept = (output - 1.0) / (2.0 / 0.75);
+
+                    assert_ne!(output, 2001.0);
+                    assert!((650.0..850.0).contains(&kept), "kept = {kept}");
+                }
+
+                network.disable_training();
+
+                assert_eq!(network.propagate_in_place(&[1.0], &mut buffer), [2001.0]);
+            }
 
-#[derive(Clone)]
-struct Layer {
-    neurons: Vec<Neuron>,
-}
+            #[test]
+            fn differs_between_calls() {
+                let mut network = network();
 
-#[derive(Clone)]
-struct Neuron {
-    bias: f32,
-    weights: Vec<f32>,
-}
+                network.enable_training(0);
 
-impl Network {
-    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
-        self.layers
-            .iter()
-            .fold(inputs, |inputs, layer| layer.propagate(&inputs))
-    }
+                let mut state = network.state();
+                let first = network.propagate_with(&mut state, vec![1.0]);
+                let second = network.propagate_with(&mut state, vec![1.0]);
 
-    pub fn random(rng: &mut dyn rand::RngCore, layers: &[LayerTopology]) -> Self {
-        assert!(layers.len() > 1);
+                assert_ne!(first, second);
 
-        let layers = layers
-            .windows(2)
-            .map(|layers| Layer::random(rng, layers[0].neurons, layers[1].neurons))
-            .collect();
+                let mut buffer = PropagateBuffer::new();
+                let first = network.propagate_in_place(&[1.0], &mut buffer).to_vec();
+                let second = network.propagate_in_place(&[1.0], &mut buffer).to_vec();
 
-        Self { layers }
-    }
-}
+                assert_ne!(first, second);
+            }
 
-impl Layer {
-    pub fn propagate(&self, inputs: &[f32]) -> Vec<f32> {
-        self.neurons
-            .iter()
-            .map(|neuron| neuron.propagate(inputs))
-            .collect()
-    }
+            #[test]
+            fn reproducible() {
+                let mut network = network();
 
-    pub fn random(
-        rng: &mut dyn rand::RngCore,
-        input_neurons: usize,
-        output_neurons: usize,
-    ) -> Self {
-        let neurons = (0..output_neurons)
-            .map(|_| Neuron::random(rng, input_neurons))
-            .collect();
+                network.enable_training(0);
 
-        Self { neurons }
-    }
-}
+                let mut state = network.state();
+                let first = network.propagate_with(&mut state, vec![1.0]);
 
-impl Neuron {
-    pub fn propagate(&self, inputs: &[f32]) -> f32 {
-        assert_eq!(inputs.len(), self.weights.len());
+                network.propagate_with(&mut state, vec![1.0]);
+                network.reset_state(&mut state);
 
-        let output = inputs
-            .iter()
-            .zip(&self.weights)
-            .map(|(input, weight)| input * weight)
-            .sum::<f32>();
+                assert_eq!(network.propagate_with(&mut state, vec![1.0]), first);
+            }
 
-        (self.bias + output).max(0.0)
-    }
+            #[test]
+            fn skipped_without_state() {
+                let mut network = network();
 
-    pub fn random(rng: &mut dyn rand::RngCore, output_size: usize) -> Self {
-        let bias = rng.gen_range(-1.0..=1.0);
+                network.enable_training(0);
 
-        let weights = (0..output_size)
-            .map(|_| rng.gen_range(-1.0..=1.0))
-            .collect();
+                assert_eq!(network.propagate(vec![1.0]), [2001.0]);
+                assert_eq!(network.propagate_batch(&[vec![1.0]]), [[2001.0]]);
+            }
 
-        Self { bias, weights }
-    }
-}
+            #[test]
+            fn topology() {
+                assert_eq!(network().topology()[1].dropout, 0.25);
+            }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use approx::assert_relative_eq;
+            #[test]
+            fn rejects_invalid_rate() {
+                let layers = NetworkBuilder::new(1)
+                    .layer(1)
+                    .dropout(1.0)
+                    .topology()
+                    .to_vec();
 
-    mod neuron {
-        use super::*;
+                assert_eq!(
+                    Network::try_from_weights(&layers, [0.0; 2]).err(),
+                    Some(NetworkError::InvalidDropout { layer: 0 })
+                );
+            }
+        }
 
-        mod random {
+        mod mutate {
             use super::*;
             use rand::SeedableRng;
             use rand_chacha::ChaCha8Rng;
 
+            fn network() -> Network {
+                NetworkBuilder::new(3)
+                    .layer(2)
+                    .kind(LayerKind::Recurrent)
+                    .output(1, Activation::Linear)
+                    .build_from_weights((0..15).map(|n| n as Float))
+            }
+
             #[test]
-            fn test() {
+            fn zero_rate() {
                 let mut rng = ChaCha8Rng::from_seed(Default::default());
-                let neuron = Neuron::random(&mut rng, 4);
+                let mut network = network();
 
-                assert_relative_eq!(neuron.bias, -0.6255188);
+                network.mutate(&mut rng, 0.0, 0.5);
 
-                assert_relative_eq!(
-                    neuron.weights.as_slice(),
-                    [0.67383957, 0.8181262, 0.26284897, 0.5238807,].as_ref()
-                );
+                assert!(network.weights().eq(self::network().weights()));
+            }
+
+            #[test]
+            fn full_rate() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut network = network();
+
+                network.mutate(&mut rng, 1.0, 0.5);
+
+                for (actual, original) in network.weights().zip(self::network().weights()) {
+                    assert_ne!(actual, original);
+                    assert!((actual - original).abs() <= 0.5);
+                }
             }
         }
 
-        mod propagate {
+        mod distance {
+            use super::*;
+
+            fn network(weights: [Float; 3]) -> Network {
+                NetworkBuilder::new(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights(weights)
+            }
+
+            #[test]
+            fn test() {
+                let a = network([0.0, 1.0, 2.0]);
+                let b = network([3.0, 1.0, -2.0]);
+
+                // sqrt((9 + 0 + 16) / 3)
+                assert_relative_eq!(a.distance(&b), 2.8867513, max_relative = TOLERANCE);
+                assert_relative_eq!(b.distance(&a), a.distance(&b));
+                assert_eq!(a.distance(&a), 0.0);
+            }
+
+            #[test]
+            fn rejects_different_topologies() {
+                let a = network([0.0; 3]);
+
+                let b = NetworkBuilder::new(1)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.0; 2]);
+
+                assert_eq!(a.try_distance(&b), Err(NetworkError::TopologyMismatch));
+            }
+        }
+
+        mod norm {
+            use super::*;
+
+            fn network() -> Network {
+                NetworkBuilder::new(1)
+                    .layer(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([1.0, -2.0, 0.0, 2.0, 4.0, -2.0, 1.0])
+            }
+
+            #[test]
+            fn test() {
+                let network = network();
+
+                assert_relative_eq!(network.l1_norm(), 12.0);
+                assert_relative_eq!(network.l2_norm(), 5.477226, max_relative = TOLERANCE);
+            }
+
+            #[test]
+            fn layer() {
+                let network = network();
+
+                assert_eq!(network.layer_l1_norm(0), Some(5.0));
+                assert_eq!(network.layer_l2_norm(0), Some(3.0));
+                assert_eq!(network.layer_l1_norm(1), Some(7.0));
+                assert_eq!(network.layer_l2_norm(2), None);
+            }
+        }
+
+        mod fingerprint {
             use super::*;
 
+            fn network(weights: [Float; 3]) -> Network {
+                NetworkBuilder::new(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights(weights)
+            }
+
             #[test]
             fn test() {
-                let neuron = Neuron {
-                    bias: 0.5,
-                    weights: vec![-0.3, 0.8],
+                let fingerprint = network([0.5, -1.0, 0.25]).fingerprint();
+
+                // Fingerprints are meant to be stored, so they mustn't change
+                assert_eq!(fingerprint, 0x82ce3c91e8d0b283);
+
+                assert_eq!(network([0.5, -1.0, 0.2500001]).fingerprint(), fingerprint);
+                assert_ne!(network([0.5, -1.0, 0.26]).fingerprint(), fingerprint);
+            }
+
+            #[test]
+            fn depends_on_shape() {
+                let a = NetworkBuilder::new(1)
+                    .layer(1)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.0; 4]);
+
+                let b = NetworkBuilder::new(3)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.0; 4]);
+
+                assert_ne!(a.fingerprint(), b.fingerprint());
+            }
+
+            #[test]
+            fn depends_on_topology() {
+                let network = |activation| {
+                    NetworkBuilder::new(2)
+                        .output(1, activation)
+                        .build_from_weights([0.5, -1.0, 0.25])
+                        .fingerprint()
                 };
 
-                assert_relative_eq!(neuron.propagate(&[-10.0, -10.0]), 0.0,);
+                let fingerprints = [
+                    network(Activation::Linear),
+                    network(Activation::Tanh),
+                    network(Activation::LeakyRelu(0.01)),
+                    network(Activation::LeakyRelu(0.02)),
+                ];
 
-                approx::assert_relative_eq!(
-                    neuron.propagate(&[0.5, 1.0]),
-                    (-0.3 * 0.5) + (0.8 * 1.0) + 0.5,
-                );
+                for (id, fingerprint) in fingerprints.iter().enumerate() {
+                    assert!(!fingerprints[..id].contains(fingerprint));
+                }
             }
         }
-    }
 
-    mod layer {
-        use super::*;
+        mod prune {
+            use super::*;
 
-        mod random {
+            #[test]
+            fn test() {
+                let mut network = Network::new(vec![Layer::Dense(DenseLayer::new(
+                    vec![0.01, 0.5],
+                    vec![vec![0.05, -0.2, 0.0], vec![-0.09, 0.3, 0.1]],
+                    Activation::Relu,
+                ))]);
+
+                let sparsity = network.prune(0.1);
+
+                assert_eq!(
+                    sparsity,
+                    Sparsity {
+                        pruned: 2,
+                        zeros: 3,
+                        total: 6
+                    }
+                );
+
+                assert_relative_eq!(sparsity.ratio(), 0.5);
+
+                let expected = [0.01, 0.0, -0.2, 0.0, 0.5, 0.0, 0.3, 0.1];
+                let actual: Vec<_> = network.weights().collect();
+
+                assert_relative_eq!(actual.as_slice(), expected.as_ref());
+            }
+
+            #[test]
+            fn recurrent() {
+                let mut network = NetworkBuilder::new(1)
+                    .layer(1)
+                    .kind(LayerKind::Recurrent)
+                    .build_from_weights([0.0, 1.0, 0.05]);
+
+                assert_eq!(network.prune(0.1).pruned, 1);
+                assert_eq!(network.weight(0, 0, 1), Some(0.0));
+            }
+        }
+
+        mod crossover {
             use super::*;
             use rand::SeedableRng;
             use rand_chacha::ChaCha8Rng;
 
+            fn network(weight: Float) -> Network {
+                NetworkBuilder::new(3)
+                    .layer(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([weight; 11])
+            }
+
             #[test]
             fn test() {
                 let mut rng = ChaCha8Rng::from_seed(Default::default());
-                let layer = Layer::random(&mut rng, 3, 2);
+                let child = network(1.0).crossover(&network(2.0), &mut rng);
+                let weights: Vec<_> = child.weights().collect();
 
-                let actual: Vec<_> = layer
-                    .neurons
-                    .iter()
-                    .map(|neuron| neuron.weights.as_slice())
-                    .collect();
+                assert!(weights.contains(&1.0));
+                assert!(weights.contains(&2.0));
+                assert!(weights.iter().all(|&w| w == 1.0 || w == 2.0));
+            }
 
-                let expected: Vec<&[f32]> = vec![
-                    &[0.67383957, 0.8181262, 0.26284897],
-                    &[-0.53516835, 0.069369674, -0.7648182],
-                ];
+            #[test]
+            fn rejects_different_topologies() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
 
-                approx::assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                let other = NetworkBuilder::new(3)
+                    .layer(2)
+                    .kind(LayerKind::Recurrent)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([2.0; 15]);
+
+                assert_eq!(
+                    network(1.0).try_crossover(&other, &mut rng).err(),
+                    Some(NetworkError::TopologyMismatch)
+                );
             }
         }
 
-        mod propagate {
+        mod set_weight {
+            use super::*;
+
+            fn network() -> Network {
+                Network::new(vec![Layer::Dense(DenseLayer::new(
+                    vec![0.1],
+                    vec![vec![0.2, 0.3]],
+                    Activation::Relu,
+                ))])
+            }
+
+            #[test]
+            fn updates_weight() {
+                let mut network = network();
+
+                network.set_weight(0, 0, 1, 0.0).unwrap();
+
+                assert_eq!(network.weight(0, 0, 0), Some(0.2));
+                assert_eq!(network.weight(0, 0, 1), Some(0.0));
+                let actual = network.propagate(vec![1.0, 1.0]);
+
+                assert_relative_eq!(actual.as_slice(), [0.3].as_ref());
+            }
+
+            #[test]
+            fn updates_bias() {
+                let mut network = network();
+
+                network.set_bias(0, 0, -1.0).unwrap();
+
+                assert_eq!(network.bias(0, 0), Some(-1.0));
+            }
+
+            #[test]
+            fn rejects_out_of_bounds() {
+                let mut network = network();
+
+                assert_eq!(network.weight(0, 0, 2), None);
+                assert_eq!(network.bias(1, 0), None);
+
+                assert_eq!(
+                    network.set_weight(0, 0, 2, 1.0),
+                    Err(NetworkError::WeightOutOfBounds {
+                        layer: 0,
+                        neuron: 0,
+                        index: 2,
+                    })
+                );
+
+                assert_eq!(
+                    network.set_bias(0, 1, 1.0),
+                    Err(NetworkError::NeuronOutOfBounds {
+                        layer: 0,
+                        neuron: 1,
+                    })
+                );
+            }
+        }
+
+        mod insert_layer {
             use super::*;
+            use crate::layer::{ScaleLayer, SumLayer};
+
+            fn network() -> Network {
+                NetworkBuilder::new(2)
+                    .layer(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.5, 1.0, 0.0, -0.5, 0.0, 1.0, 0.0, 1.0, -1.0])
+            }
 
             #[test]
             fn test() {
-                let neurons = vec![
-                    Neuron {
-                        bias: 0.1,
-                        weights: vec![0.2, 0.3, 0.4],
-                    },
-                    Neuron {
-                        bias: 0.5,
-                        weights: vec![0.6, 0.7, 0.8],
-                    },
-                ];
-                let input = vec![-0.5, 0.0, 0.5];
+                let mut network = network();
 
-                let layer = Layer {
-                    neurons: neurons.clone(),
-                };
+                network.insert_layer(1, ScaleLayer(vec![2.0, 3.0]));
 
-                let actual = layer.propagate(&input);
-                let expected = vec![neurons[0].propagate(&input), neurons[1].propagate(&input)];
+                // Hidden layer yields [1.5, 0.5], scaled to [3.0, 1.5]
+                let actual = network.propagate(vec![1.0, 1.0]);
 
-                assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                assert_relative_eq!(actual.as_slice(), [1.5].as_ref());
+
+                assert_eq!(network.num_parameters(), 11);
+                assert_eq!(network.topology()[2].kind, LayerKind::Custom);
+                assert_eq!(
+                    network.weights().skip(6).take(2).collect::<Vec<_>>(),
+                    [2.0, 3.0]
+                );
+
+                for weight in network.weights_mut().skip(6).take(2) {
+                    *weight = 1.0;
+                }
+
+                let actual = network.clone().propagate(vec![1.0, 1.0]);
+
+                assert_relative_eq!(actual.as_slice(), [1.0].as_ref());
             }
-        }
-    }
 
-    mod network {
-        use super::*;
+            #[test]
+            fn keeps_state() {
+                let mut network = NetworkBuilder::new(2)
+                    .output(2, Activation::Linear)
+                    .build_from_weights([0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
 
-        mod random {
+                network.insert_layer(1, SumLayer(2));
+
+                let mut state = network.state();
+
+                assert_eq!(
+                    network.propagate_with(&mut state, vec![1.0, 2.0]),
+                    [1.0, 2.0]
+                );
+                assert_eq!(
+                    network.propagate_with(&mut state, vec![1.0, 2.0]),
+                    [2.0, 4.0]
+                );
+
+                // Stateless propagation starts from a blank state each time
+                assert_eq!(network.propagate(vec![1.0, 2.0]), [1.0, 2.0]);
+                assert_eq!(network.propagate(vec![1.0, 2.0]), [1.0, 2.0]);
+
+                network.reset_state(&mut state);
+
+                assert_eq!(
+                    network.propagate_with(&mut state, vec![1.0, 2.0]),
+                    [1.0, 2.0]
+                );
+            }
+
+            #[test]
+            fn shifts_skip_connections() {
+                let mut network = NetworkBuilder::new(2)
+                    .layer(2)
+                    .output(2, Activation::Linear)
+                    .skip(1)
+                    .build_from_weights([
+                        0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+                    ]);
+
+                network.insert_layer(0, ScaleLayer(vec![2.0, 3.0]));
+
+                // Skip still comes from the hidden layer, not the custom one
+                assert_eq!(network.topology()[3].skip, Some(2));
+                assert_eq!(network.propagate(vec![1.0, 2.0]), [2.0, 0.0]);
+            }
+
+            #[test]
+            fn rejects_wrong_sizes() {
+                let mut network = network();
+
+                assert_eq!(
+                    network.try_insert_layer(3, ScaleLayer(vec![1.0])),
+                    Err(NetworkError::LayerOutOfBounds { layer: 3 })
+                );
+
+                assert_eq!(
+                    network.try_insert_layer(2, ScaleLayer(vec![1.0, 1.0])),
+                    Err(NetworkError::InputSizeMismatch {
+                        expected: 1,
+                        actual: 2,
+                    })
+                );
+
+                assert_eq!(
+                    network.try_insert_layer(0, ScaleLayer(vec![1.0])),
+                    Err(NetworkError::InputSizeMismatch {
+                        expected: 2,
+                        actual: 1,
+                    })
+                );
+            }
+
+            #[test]
+            fn rejects_matrix_accessors() {
+                let mut network = network();
+
+                network.insert_layer(2, ScaleLayer(vec![1.0]));
+
+                assert_eq!(network.bias(2, 0), None);
+
+                assert_eq!(
+                    network.set_bias(2, 0, 1.0),
+                    Err(NetworkError::UnsupportedLayer { layer: 2 })
+                );
+            }
+
+            #[test]
+            fn rejects_custom_topology() {
+                let topology = network().topology().to_vec();
+                let mut layers = topology.clone();
+
+                layers[1].kind = LayerKind::Custom;
+
+                assert_eq!(
+                    Network::try_from_weights(&layers, [0.0; 9]).unwrap_err(),
+                    NetworkError::UnsupportedLayer { layer: 0 }
+                );
+            }
+        }
+
+        mod grow_neuron {
             use super::*;
             use rand::SeedableRng;
             use rand_chacha::ChaCha8Rng;
 
+            fn network() -> Network {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                NetworkBuilder::new(3)
+                    .layer(4)
+                    .layer(2)
+                    .output(1, Activation::Tanh)
+                    .build(&mut rng)
+            }
+
             #[test]
             fn test() {
                 let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut network = network();
+                let expected = network.propagate(vec![0.5, -0.5, 1.0]);
 
-                let network = Network::random(
-                    &mut rng,
-                    &[
-                        LayerTopology { neurons: 3 },
-                        LayerTopology { neurons: 2 },
-                        LayerTopology { neurons: 1 },
-                    ],
+                network.grow_neuron(0, &mut rng);
+
+                assert_eq!(network.topology()[1].neurons, 5);
+                assert_eq!(
+                    network.num_parameters(),
+                    5 * (1 + 3) + 2 * (1 + 5) + (1 + 2)
                 );
 
-                assert_eq!(network.layers.len(), 2);
-                assert_eq!(network.layers[0].neurons.len(), 2);
+                // Outgoing weights of the new neuron start at zero
+                assert_eq!(network.weight(1, 0, 4), Some(0.0));
+                assert_eq!(network.weight(1, 1, 4), Some(0.0));
+                assert_eq!(network.propagate(vec![0.5, -0.5, 1.0]), expected);
+            }
 
-                approx::assert_relative_eq!(
-                    network.layers[0].neurons[0].weights.as_slice(),
-                    &[0.67383957, 0.8181262, 0.26284897].as_slice()
+            #[test]
+            fn rejects_unsupported_layers() {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut network = network();
+
+                assert_eq!(
+                    network.try_grow_neuron(2, &mut rng),
+                    Err(NetworkError::UnsupportedLayer { layer: 2 })
                 );
 
-                approx::assert_relative_eq!(
-                    network.layers[0].neurons[1].weights.as_slice(),
-                    &[-0.53516835, 0.069369674, -0.7648182].as_slice()
+                assert_eq!(
+                    network.try_grow_neuron(3, &mut rng),
+                    Err(NetworkError::LayerOutOfBounds { layer: 3 })
                 );
 
-                assert_eq!(network.layers[1].neurons.len(), 1);
+                let mut network = NetworkBuilder::new(2)
+                    .layer(2)
+                    .output(2, Activation::Linear)
+                    .skip(1)
+                    .build_from_weights([0.0; 12]);
 
-                approx::assert_relative_eq!(
-                    network.layers[1].neurons[0].weights.as_slice(),
-                    &[-0.48879617, -0.19277132].as_slice()
+                assert_eq!(
+                    network.try_grow_neuron(0, &mut rng),
+                    Err(NetworkError::UnsupportedLayer { layer: 0 })
                 );
             }
         }
 
-        mod propagate {
+        mod shrink_neuron {
             use super::*;
 
+            fn network() -> Network {
+                NetworkBuilder::new(2)
+                    .layer(3)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([
+                        0.1, 0.2, 0.3, // neuron #0
+                        0.4, 0.5, 0.6, // neuron #1
+                        0.7, 0.8, 0.9, // neuron #2
+                        1.0, 2.0, 3.0, 4.0, // output
+                    ])
+            }
+
             #[test]
             fn test() {
-                let layers = vec![
-                    Layer {
-                        neurons: vec![
-                            Neuron {
-                                bias: 0.1,
-                                weights: vec![0.2, 0.3, 0.4],
-                            },
-                            Neuron {
-                                bias: 0.5,
-                                weights: vec![0.6, 0.7, 0.8],
-                            },
-                        ],
-                    },
-                    Layer {
-                        neurons: vec![Neuron {
-                            bias: 0.2,
-                            weights: vec![-0.5, 0.5],
-                        }],
-                    },
-                ];
+                let mut network = network();
 
-                let network = Network {
-                    layers: layers.clone(),
-                };
+                network.shrink_neuron(0, 1);
 
-                let actual = network.propagate(vec![0.5, 0.6, 0.7]);
-                let expected = layers[1].propagate(&layers[0].propagate(&[0.5, 0.6, 0.7]));
+                let expected = NetworkBuilder::new(2)
+                    .layer(2)
+                    .output(1, Activation::Linear)
+                    .build_from_weights([0.1, 0.2, 0.3, 0.7, 0.8, 0.9, 1.0, 2.0, 4.0]);
 
-                assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                assert_eq!(network, expected);
+            }
+
+            #[test]
+            fn rejects_invalid_neurons() {
+                let mut network = network();
+
+                assert_eq!(
+                    network.try_shrink_neuron(0, 3),
+                    Err(NetworkError::NeuronOutOfBounds {
+                        layer: 0,
+                        neuron: 3
+                    })
+                );
+
+                network.shrink_neuron(0, 0);
+                network.shrink_neuron(0, 0);
+
+                assert_eq!(
+                    network.try_shrink_neuron(0, 0),
+                    Err(NetworkError::LastNeuron { layer: 0 })
+                );
+            }
+        }
+
+        mod try_propagate {
+            use super::*;
+
+            fn network() -> Network {
+                Network::new(vec![Layer::Dense(DenseLayer::new(
+                    vec![0.1],
+                    vec![vec![0.2, 0.3]],
+                    Activation::Relu,
+                ))])
+            }
+
+            #[test]
+            fn rejects_wrong_input_size() {
+                assert_eq!(
+                    network().try_propagate(vec![1.0, 2.0, 3.0]),
+                    Err(NetworkError::InputSizeMismatch {
+                        expected: 2,
+                        actual: 3,
+                    })
+                );
+            }
+
+            #[test]
+            fn propagates_correct_input_size() {
+                let actual = network().try_propagate(vec![1.0, 2.0]).unwrap();
+
+                assert_relative_eq!(actual.as_slice(), [0.9].as_ref());
             }
         }
     }
@@ -0,0 +1,49 @@
+//! Floating-point functions that `core` lacks, provided by `libm` when
+//! building without the `std` feature.
+//!
+//! When something else links `std` anyway (e.g. dev-dependencies in tests),
+//! its inherent float methods take precedence and leave these unused.
+
+#[allow(dead_code)]
+pub(crate) trait FloatExt {
+    fn exp(self) -> Self;
+    fn tanh(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn round(self) -> Self;
+}
+
+impl FloatExt for f32 {
+    fn exp(self) -> Self {
+        libm::expf(self)
+    }
+
+    fn tanh(self) -> Self {
+        libm::tanhf(self)
+    }
+
+    fn sqrt(self) -> Self {
+        libm::sqrtf(self)
+    }
+
+    fn round(self) -> Self {
+        libm::roundf(self)
+    }
+}
+
+impl FloatExt for f64 {
+    fn exp(self) -> Self {
+        libm::exp(self)
+    }
+
+    fn tanh(self) -> Self {
+        libm::tanh(self)
+    }
+
+    fn sqrt(self) -> Self {
+        libm::sqrt(self)
+    }
+
+    fn round(self) -> Self {
+        libm::round(self)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::*;
+
+/// Rescales each input into `[0, 1]` given the range it's expected to span,
+/// e.g. so that eye cells and speed sensors - whose raw values differ by
+/// orders of magnitude - weigh in alike; see [`Network::set_normalizer()`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Normalizer {
+    min: Vec<Float>,
+    max: Vec<Float>,
+}
+
+impl Normalizer {
+    /// Creates a normalizer mapping `min[i]..=max[i]` onto `0..=1` for each
+    /// `i`-th input.
+    pub fn new(min: Vec<Float>, max: Vec<Float>) -> Self {
+        assert_eq!(min.len(), max.len(), "min and max have different lengths");
+
+        Self { min, max }
+    }
+
+    /// Creates a normalizer for the range inputs span across `samples` (e.g.
+    /// recorded during a simulation).
+    ///
+    /// Panics if there are no samples or if they have different sizes.
+    pub fn fit(samples: &[Vec<Float>]) -> Self {
+        let (first, rest) = samples.split_first().expect("got no samples");
+        let mut min = first.clone();
+        let mut max = first.clone();
+
+        for sample in rest {
+            assert_eq!(sample.len(), first.len(), "samples have different sizes");
+
+            for ((min, max), &value) in min.iter_mut().zip(&mut max).zip(sample) {
+                *min = min.min(value);
+                *max = max.max(value);
+            }
+        }
+
+        Self { min, max }
+    }
+
+    /// Returns the number of inputs this normalizer expects.
+    pub fn len(&self) -> usize {
+        self.min.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.is_empty()
+    }
+
+    pub fn min(&self) -> &[Float] {
+        &self.min
+    }
+
+    pub fn max(&self) -> &[Float] {
+        &self.max
+    }
+
+    /// Rescales `inputs` in place; values outside of the fitted range get
+    /// clamped, and inputs whose range is empty become 0.
+    pub fn normalize(&self, inputs: &mut [Float]) {
+        assert_eq!(inputs.len(), self.len());
+
+        for (input, scale) in inputs.iter_mut().zip(self.scales()) {
+            *input = (scale.1 * (*input - scale.0)).clamp(0.0, 1.0);
+        }
+    }
+
+    /// Returns `(offset, factor)` of each input, so that the normalized input
+    /// is `factor * (input - offset)` (before clamping).
+    pub(crate) fn scales(&self) -> impl Iterator<Item = (Float, Float)> + '_ {
+        self.min.iter().zip(&self.max).map(|(&min, &max)| {
+            let range = max - min;
+
+            (min, if range > 0.0 { 1.0 / range } else { 0.0 })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    mod fit {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let normalizer = Normalizer::fit(&[vec![1.0, -5.0], vec![3.0, 5.0], vec![2.0, 0.0]]);
+
+            assert_eq!(normalizer.min(), [1.0, -5.0]);
+            assert_eq!(normalizer.max(), [3.0, 5.0]);
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_no_samples() {
+            Normalizer::fit(&[]);
+        }
+    }
+
+    mod normalize {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let normalizer = Normalizer::new(vec![1.0, -5.0, 2.0], vec![3.0, 5.0, 2.0]);
+            let mut inputs = [2.5, 10.0, 7.0];
+
+            normalizer.normalize(&mut inputs);
+
+            assert_relative_eq!(inputs.as_ref(), [0.75, 1.0, 0.0].as_ref());
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_on_wrong_input_size() {
+            Normalizer::new(vec![0.0], vec![1.0]).normalize(&mut [0.5, 0.5]);
+        }
+    }
+}
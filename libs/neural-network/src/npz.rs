@@ -0,0 +1,603 @@
+//! Export to NumPy's `.npz`, so that weights can be analyzed with NumPy or
+//! Matplotlib (`numpy.load("network.npz")`) without a custom parser.
+//!
+//! An `.npz` is a zip archive of `.npy` files; we write it uncompressed (same
+//! as `numpy.savez()`), which keeps both formats simple enough to encode by
+//! hand.
+
+use crate::*;
+use std::{fs, io, path::Path};
+
+/// Array stored in an `.npz`, flattened in row-major order
+struct Array {
+    name: String,
+    shape: Vec<usize>,
+    data: Vec<Float>,
+}
+
+impl Network {
+    /// Saves this network's parameters as a NumPy `.npz` archive containing,
+    /// for each `id`-th layer:
+    ///
+    /// - `layer{id}_weights`, shaped `[outputs, inputs]` (for LSTM and GRU
+    ///   layers, rows of all the gates are stacked on top of each other),
+    /// - `layer{id}_biases`, shaped `[outputs]`, unless the layer has no
+    ///   biases,
+    /// - `layer{id}_alpha`, shaped `[1]`, if the layer uses
+    ///   [`Activation::Prelu`],
+    /// - `layer{id}_rates`, shaped like the weights, for learning rates of
+    ///   plastic layers.
+    ///
+    /// If the network has a [`Normalizer`], its ranges get saved as
+    /// `normalizer_min` and `normalizer_max`.
+    ///
+    /// Custom layers aren't supported.
+    pub fn to_npz(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut arrays = Vec::new();
+
+        if let Some(normalizer) = &self.normalizer {
+            arrays.push(Array {
+                name: String::from("normalizer_min"),
+                shape: vec![normalizer.len()],
+                data: normalizer.min().to_vec(),
+            });
+
+            arrays.push(Array {
+                name: String::from("normalizer_max"),
+                shape: vec![normalizer.len()],
+                data: normalizer.max().to_vec(),
+            });
+        }
+
+        for (id, layer) in self.layers.iter().enumerate() {
+            if layer.is_custom() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    NetworkError::UnsupportedLayer { layer: id },
+                ));
+            }
+
+            let params = layer.params();
+
+            arrays.push(Array {
+                name: format!("layer{id}_weights"),
+                shape: vec![params.outputs(), params.inputs()],
+                data: (0..params.outputs())
+                    .flat_map(|neuron| params.row(neuron))
+                    .copied()
+                    .collect(),
+            });
+
+            if params.has_bias() {
+                arrays.push(Array {
+                    name: format!("layer{id}_biases"),
+                    shape: vec![params.outputs()],
+                    data: (0..params.outputs())
+                        .map(|neuron| params.bias(neuron).unwrap())
+                        .collect(),
+                });
+            }
+
+            if params.activation() == Activation::Prelu {
+                arrays.push(Array {
+                    name: format!("layer{id}_alpha"),
+                    shape: vec![1],
+                    data: vec![params.prelu_alpha()],
+                });
+            }
+
+            if let Layer::Plastic(layer) = layer {
+                arrays.push(Array {
+                    name: format!("layer{id}_rates"),
+                    shape: vec![params.outputs(), params.inputs()],
+                    data: layer.rates().to_vec(),
+                });
+            }
+        }
+
+        fs::write(path, encode(&arrays))
+    }
+
+    /// Loads a network saved with [`Self::to_npz()`]; since the archive holds
+    /// only parameters, the topology has to be provided, same as for
+    /// [`Self::from_weights()`].
+    ///
+    /// Arrays can be stored either as `float32` or `float64`, so an archive
+    /// modified with NumPy (e.g. `numpy.savez("network.npz", **arrays)`)
+    /// loads as well; compressed archives aren't supported, though.
+    ///
+    /// Weights that the topology masks out stay zero, whatever the archive
+    /// says.
+    pub fn from_npz(layers: &[LayerTopology], path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut network =
+            Self::try_from_weights(layers, vec![0.0; Self::num_parameters_of(layers)])
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+        let mut arrays = decode(&fs::read(path)?)?;
+        let has_normalizer = arrays.iter().any(|array| array.name == "normalizer_min");
+
+        let mut take = |name: &str, shape: &[usize]| -> io::Result<Vec<Float>> {
+            let position = arrays
+                .iter()
+                .position(|array| array.name == name)
+                .ok_or_else(|| invalid(format!("missing array `{name}`")))?;
+
+            let array = arrays.swap_remove(position);
+
+            if array.shape != shape {
+                return Err(invalid(format!(
+                    "expected array `{name}` to be shaped {shape:?}, got {:?}",
+                    array.shape
+                )));
+            }
+
+            Ok(array.data)
+        };
+
+        for (id, layer) in network.layers.iter_mut().enumerate() {
+            if let Layer::Plastic(layer) = layer {
+                let shape = [layer.outputs(), layer.inputs()];
+
+                layer
+                    .rates_mut()
+                    .copy_from_slice(&take(&format!("layer{id}_rates"), &shape)?);
+            }
+
+            let params = layer.params_mut();
+            let (inputs, outputs) = (params.inputs(), params.outputs());
+
+            let weights = take(&format!("layer{id}_weights"), &[outputs, inputs])?;
+
+            let biases = if params.has_bias() {
+                take(&format!("layer{id}_biases"), &[outputs])?
+            } else {
+                Vec::new()
+            };
+
+            let alpha = if params.activation() == Activation::Prelu {
+                take(&format!("layer{id}_alpha"), &[1])?
+            } else {
+                Vec::new()
+            };
+
+            // Same order as `DenseLayer::weights()`, which skips masked out
+            // weights
+            let mask = params.mask().map(<[bool]>::to_vec);
+            let mask = mask.as_deref();
+
+            let values = (0..outputs)
+                .flat_map(|neuron| {
+                    let row = weights[neuron * inputs..][..inputs]
+                        .iter()
+                        .enumerate()
+                        .filter(move |&(index, _)| {
+                            mask.is_none_or(|mask| mask[neuron * inputs + index])
+                        })
+                        .map(|(_, weight)| weight);
+
+                    biases.get(neuron).into_iter().chain(row)
+                })
+                .chain(&alpha);
+
+            for (weight, value) in params.weights_mut().zip(values) {
+                *weight = *value;
+            }
+        }
+
+        if has_normalizer {
+            let inputs = network.input_size();
+            let min = take("normalizer_min", &[inputs])?;
+            let max = take("normalizer_max", &[inputs])?;
+
+            network.set_normalizer(Some(Normalizer::new(min, max)));
+        }
+
+        Ok(network)
+    }
+}
+
+/// Encodes `arrays` as a zip archive of `.npy` files, stored uncompressed.
+fn encode(arrays: &[Array]) -> Vec<u8> {
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+
+    for array in arrays {
+        let name = format!("{}.npy", array.name);
+        let data = encode_npy(array);
+        let offset = archive.len() as u32;
+
+        // Fields shared by the local header and the central directory:
+        // version needed (2.0), flags, method (stored), time, date (1980-01-01),
+        // CRC-32 and sizes
+        let mut header = Vec::new();
+
+        header.extend_from_slice(&20u16.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&0x21u16.to_le_bytes());
+        header.extend_from_slice(&crc32(&data).to_le_bytes());
+        header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+
+        archive.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(&data);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&header);
+
+        // Comment length, disk number, internal and external attributes
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let offset = archive.len() as u32;
+
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&(arrays.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(arrays.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    archive
+}
+
+/// Encodes `array` as an `.npy` file (format version 1.0).
+fn encode_npy(array: &Array) -> Vec<u8> {
+    let descr = if core::mem::size_of::<Float>() == 8 {
+        "<f8"
+    } else {
+        "<f4"
+    };
+
+    // Written the way Python writes tuples, i.e. `(3,)` and `(2, 3)`
+    let shape = match array.shape.as_slice() {
+        [dim] => format!("({dim},)"),
+        shape => format!("{shape:?}").replace('[', "(").replace(']', ")"),
+    };
+
+    let mut header = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+
+    // Magic, version and header length take 10 bytes, and the whole preamble
+    // has to be padded to a multiple of 64 bytes, ending with a newline
+    let len = 10 + header.len() + 1;
+
+    header.extend(core::iter::repeat_n(' ', len.next_multiple_of(64) - len));
+    header.push('\n');
+
+    let mut npy = Vec::new();
+
+    npy.extend_from_slice(b"\x93NUMPY\x01\x00");
+    npy.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    npy.extend_from_slice(header.as_bytes());
+    npy.extend(array.data.iter().flat_map(|x| x.to_le_bytes()));
+    npy
+}
+
+/// Decodes arrays out of an uncompressed zip archive of `.npy` files.
+fn decode(archive: &[u8]) -> io::Result<Vec<Array>> {
+    // End of central directory record is the last thing in the archive,
+    // followed only by a comment of up to 64 KiB
+    let end = (0..=archive.len().saturating_sub(22))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|&offset| u32_at(archive, offset).ok() == Some(0x0605_4b50))
+        .ok_or_else(|| invalid("not a zip archive"))?;
+
+    let entries = u16_at(archive, end + 10)?;
+    let mut offset = u32_at(archive, end + 16)? as usize;
+    let mut arrays = Vec::new();
+
+    for _ in 0..entries {
+        if u32_at(archive, offset)? != 0x0201_4b50 {
+            return Err(invalid("corrupted central directory"));
+        }
+
+        let method = u16_at(archive, offset + 10)?;
+        let size = u32_at(archive, offset + 20)? as usize;
+        let name_len = u16_at(archive, offset + 28)? as usize;
+        let extra_len = u16_at(archive, offset + 30)? as usize;
+        let comment_len = u16_at(archive, offset + 32)? as usize;
+        let header = u32_at(archive, offset + 42)? as usize;
+        let name = bytes_at(archive, offset + 46, name_len)?;
+
+        let name = core::str::from_utf8(name)
+            .map_err(|_| invalid("array name isn't valid UTF-8"))?
+            .trim_end_matches(".npy");
+
+        if method != 0 {
+            return Err(invalid(format!(
+                "array `{name}` is compressed, which isn't supported"
+            )));
+        }
+
+        if u32_at(archive, header)? != 0x0403_4b50 {
+            return Err(invalid("corrupted local header"));
+        }
+
+        let data = header
+            + 30
+            + u16_at(archive, header + 26)? as usize
+            + u16_at(archive, header + 28)? as usize;
+
+        arrays.push(decode_npy(name, bytes_at(archive, data, size)?)?);
+        offset += 46 + name_len + extra_len + comment_len;
+    }
+
+    Ok(arrays)
+}
+
+/// Decodes an `.npy` file holding a C-ordered array of `float32`s or
+/// `float64`s.
+fn decode_npy(name: &str, npy: &[u8]) -> io::Result<Array> {
+    if bytes_at(npy, 0, 6)? != b"\x93NUMPY" {
+        return Err(invalid(format!("array `{name}` isn't an .npy file")));
+    }
+
+    // Version 1.0 stores the header's length as u16, later ones as u32
+    let (header_start, header_len) = match bytes_at(npy, 6, 1)?[0] {
+        1 => (10, u16_at(npy, 8)? as usize),
+        _ => (12, u32_at(npy, 8)? as usize),
+    };
+
+    let malformed = || invalid(format!("array `{name}` has a malformed header"));
+
+    let header =
+        core::str::from_utf8(bytes_at(npy, header_start, header_len)?).map_err(|_| malformed())?;
+
+    let descr = header_value(header, "descr")
+        .and_then(|value| value.strip_prefix('\''))
+        .and_then(|value| value.split('\'').next())
+        .ok_or_else(malformed)?;
+
+    let fortran_order = header_value(header, "fortran_order").ok_or_else(malformed)?;
+
+    let shape: Vec<usize> = header_value(header, "shape")
+        .and_then(|value| value.strip_prefix('('))
+        .and_then(|value| value.split(')').next())
+        .ok_or_else(malformed)?
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse().map_err(|_| malformed()))
+        .collect::<io::Result<_>>()?;
+
+    if fortran_order.starts_with("True") && shape.len() > 1 {
+        return Err(invalid(format!(
+            "array `{name}` is in Fortran order, which isn't supported"
+        )));
+    }
+
+    let len = shape.iter().product::<usize>();
+    let data = &npy[header_start + header_len..];
+
+    let data = match descr {
+        "<f4" => bytes_at(data, 0, 4 * len)?
+            .chunks(4)
+            .map(|x| f32::from_le_bytes(x.try_into().unwrap()) as Float)
+            .collect(),
+
+        "<f8" => bytes_at(data, 0, 8 * len)?
+            .chunks(8)
+            .map(|x| f64::from_le_bytes(x.try_into().unwrap()) as Float)
+            .collect(),
+
+        descr => {
+            return Err(invalid(format!(
+                "array `{name}` has unsupported type `{descr}`, expected `<f4` or `<f8`"
+            )))
+        }
+    };
+
+    Ok(Array {
+        name: String::from(name),
+        shape,
+        data,
+    })
+}
+
+/// Returns what follows `'key':` in a `.npy` header (a Python dict literal).
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let (_, value) = header.split_once(&format!("'{key}':"))?;
+
+    Some(value.trim_start())
+}
+
+fn bytes_at(bytes: &[u8], offset: usize, len: usize) -> io::Result<&[u8]> {
+    bytes
+        .get(offset..)
+        .and_then(|bytes| bytes.get(..len))
+        .ok_or_else(|| invalid("unexpected end of file"))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> io::Result<u16> {
+    Ok(u16::from_le_bytes(
+        bytes_at(bytes, offset, 2)?.try_into().unwrap(),
+    ))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(
+        bytes_at(bytes, offset, 4)?.try_into().unwrap(),
+    ))
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// CRC-32 (as used by zip), computed bit by bit - archives are small enough
+/// that a lookup table isn't worth it.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// Returns a path in the temporary directory, unique to given test.
+    fn path(test: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "lib-neural-network-{test}-{}.npz",
+            std::process::id()
+        ))
+    }
+
+    fn roundtrip(test: &str, network: &Network) -> Network {
+        let path = path(test);
+
+        network.to_npz(&path).unwrap();
+
+        let actual = Network::from_npz(&network.topology(), &path);
+
+        fs::remove_file(&path).unwrap();
+        actual.unwrap()
+    }
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let network = NetworkBuilder::new(3)
+            .layer(4)
+            .activation(Activation::Tanh)
+            .output(2, Activation::Sigmoid)
+            .build(&mut rng);
+
+        assert_eq!(roundtrip("test", &network), network);
+    }
+
+    #[test]
+    fn bias_alpha_and_normalizer() {
+        let mut network = NetworkBuilder::new(2)
+            .layer(2)
+            .bias(false)
+            .output(1, Activation::Prelu)
+            .build_from_weights([0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8]);
+
+        network.set_normalizer(Some(Normalizer::new(vec![0.0, -1.0], vec![1.0, 1.0])));
+
+        assert_eq!(roundtrip("bias_alpha_and_normalizer", &network), network);
+    }
+
+    #[test]
+    fn recurrent_layers() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let network = NetworkBuilder::new(2)
+            .layer(3)
+            .kind(LayerKind::Lstm)
+            .output(1, Activation::Linear)
+            .build(&mut rng);
+
+        assert_eq!(roundtrip("recurrent_layers", &network), network);
+    }
+
+    #[test]
+    fn mask() {
+        let network = NetworkBuilder::new(2)
+            .output(2, Activation::Linear)
+            .mask(vec![false, true, true, false])
+            .build_from_weights([0.1, 0.2, 0.3, 0.4]);
+
+        assert_eq!(roundtrip("mask", &network), network);
+    }
+
+    #[test]
+    fn plastic_layers() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let network = NetworkBuilder::new(2)
+            .layer(3)
+            .kind(LayerKind::Plastic)
+            .output(1, Activation::Linear)
+            .build(&mut rng);
+
+        assert_eq!(roundtrip("plastic_layers", &network), network);
+    }
+
+    #[test]
+    fn rejects_wrong_topology() {
+        let network = NetworkBuilder::new(2)
+            .output(2, Activation::Linear)
+            .build_from_weights([0.0; 6]);
+
+        let path = path("rejects_wrong_topology");
+
+        network.to_npz(&path).unwrap();
+
+        let builder = NetworkBuilder::new(3).output(2, Activation::Linear);
+        let err = Network::from_npz(builder.topology(), &path).unwrap_err();
+
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "expected array `layer0_weights` to be shaped [2, 3], got [2, 2]"
+        );
+    }
+
+    #[test]
+    fn npy_header() {
+        let npy = encode_npy(&Array {
+            name: String::from("weights"),
+            shape: vec![2, 3],
+            data: vec![0.0; 6],
+        });
+
+        let len = u16_at(&npy, 8).unwrap() as usize;
+        let header = core::str::from_utf8(&npy[10..10 + len]).unwrap();
+
+        assert!(header.starts_with("{'descr': '<f"));
+        assert!(header.contains("'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(header.ends_with(" \n"));
+        assert_eq!((10 + len) % 64, 0);
+        assert_eq!(npy.len(), 10 + len + 6 * core::mem::size_of::<Float>());
+    }
+
+    #[test]
+    fn decodes_float64() {
+        let mut npy = b"\x93NUMPY\x01\x00".to_vec();
+        let header = "{'descr': '<f8', 'fortran_order': False, 'shape': (2,), }";
+
+        npy.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        npy.extend_from_slice(header.as_bytes());
+        npy.extend([0.5f64, -2.0].iter().flat_map(|x| x.to_le_bytes()));
+
+        let array = decode_npy("biases", &npy).unwrap();
+
+        assert_eq!(array.shape, [2]);
+        assert_eq!(array.data, [0.5, -2.0]);
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+}
@@ -0,0 +1,628 @@
+//! Export to ONNX, so that evolved networks can be inspected with tools such
+//! as Netron or loaded into Python (e.g. through `onnxruntime`).
+//!
+//! The protobuf messages are encoded by hand, since we only need a handful of
+//! them (see `onnx.proto` for their field numbers).
+
+use crate::*;
+
+/// ONNX's `TensorProto.DataType` matching [`Float`]
+const FLOAT: u64 = if core::mem::size_of::<Float>() == 8 {
+    11
+} else {
+    1
+};
+
+const INT64: u64 = 7;
+
+impl Network {
+    /// Encodes this network as an ONNX model (opset 13) with a single input
+    /// called `input` and a single output called `output`, both shaped
+    /// `[1, size]`.
+    ///
+    /// Only dense and convolutional layers are supported; recurrent and
+    /// plastic layers carry state between calls, which an ONNX graph can't
+    /// express. Neither
+    /// is normalization, which ONNX got only in later opsets, nor are custom
+    /// layers.
+    pub fn to_onnx(&self) -> Result<Vec<u8>, NetworkError> {
+        let mut graph = Message::default();
+        let mut input = String::from("input");
+
+        if let Some(normalizer) = &self.normalizer {
+            input = normalizer.export(&mut graph, &input);
+        }
+
+        // Skip connections from the input layer see the inputs normalized
+        let inputs = input.clone();
+
+        for (id, layer) in self.layers.iter().enumerate() {
+            if layer.is_custom() || layer.params().normalizes() {
+                return Err(NetworkError::UnsupportedLayer { layer: id });
+            }
+
+            let params = layer.params();
+            let weights = params.weights_tensor(id);
+            let biases = params.biases_tensor(id);
+
+            let linear = match layer {
+                Layer::Dense(_) => {
+                    graph.message(5, &weights.tensor(&[params.outputs(), params.inputs()]));
+                    graph.message(5, &biases.tensor(&[params.outputs()]));
+
+                    let output = format!("layer{id}_linear");
+
+                    graph.message(
+                        1,
+                        Node::new("Gemm", &[&input, &weights.name, &biases.name], &output)
+                            .int(b"transB", 1)
+                            .message(),
+                    );
+
+                    output
+                }
+
+                Layer::Convolution(layer) => {
+                    let convolution = layer.convolution();
+                    let height = layer.inputs() / (convolution.channels * convolution.width);
+
+                    let image = format!("layer{id}_image");
+                    let image_shape = format!("layer{id}_image_shape");
+                    let convolved = format!("layer{id}_convolved");
+                    let output_shape = format!("layer{id}_output_shape");
+                    let output = format!("layer{id}_linear");
+
+                    graph.message(
+                        5,
+                        &shape_tensor(
+                            &image_shape,
+                            &[1, convolution.channels, height, convolution.width],
+                        ),
+                    );
+
+                    graph.message(5, &shape_tensor(&output_shape, &[1, layer.outputs()]));
+
+                    graph.message(
+                        5,
+                        &weights.tensor(&[
+                            convolution.filters,
+                            convolution.channels,
+                            convolution.kernel_height,
+                            convolution.kernel_width,
+                        ]),
+                    );
+
+                    graph.message(5, &biases.tensor(&[convolution.filters]));
+
+                    graph.message(
+                        1,
+                        Node::new("Reshape", &[&input, &image_shape], &image).message(),
+                    );
+
+                    graph.message(
+                        1,
+                        Node::new("Conv", &[&image, &weights.name, &biases.name], &convolved)
+                            .message(),
+                    );
+
+                    graph.message(
+                        1,
+                        Node::new("Reshape", &[&convolved, &output_shape], &output).message(),
+                    );
+
+                    output
+                }
+
+                _ => return Err(NetworkError::UnsupportedLayer { layer: id }),
+            };
+
+            let linear = match self.skip(id) {
+                Some(from) => {
+                    let source = match from {
+                        0 => inputs.clone(),
+                        from => format!("layer{}", from - 1),
+                    };
+
+                    let output = format!("layer{id}_skip");
+
+                    graph.message(1, Node::new("Add", &[&linear, &source], &output).message());
+                    output
+                }
+
+                None => linear,
+            };
+
+            input = format!("layer{id}");
+
+            let node = match params.activation() {
+                Activation::Relu => Node::new("Relu", &[&linear], &input),
+
+                Activation::LeakyRelu(alpha) => {
+                    Node::new("LeakyRelu", &[&linear], &input).float(b"alpha", alpha)
+                }
+
+                Activation::Prelu => {
+                    let slope = Tensor {
+                        name: format!("layer{id}_slope"),
+                        data: vec![params.prelu_alpha()],
+                    };
+
+                    graph.message(5, &slope.tensor(&[1]));
+                    Node::new("PRelu", &[&linear, &slope.name], &input)
+                }
+
+                Activation::Sigmoid => Node::new("Sigmoid", &[&linear], &input),
+                Activation::Tanh => Node::new("Tanh", &[&linear], &input),
+                Activation::Linear => Node::new("Identity", &[&linear], &input),
+                Activation::Softmax => Node::new("Softmax", &[&linear], &input).int(b"axis", 1),
+            };
+
+            graph.message(1, node.message());
+        }
+
+        graph.message(1, Node::new("Identity", &[&input], "output").message());
+        graph.bytes(2, b"network");
+        graph.message(11, &value_info("input", self.input_size()));
+        graph.message(12, &value_info("output", self.output_size()));
+
+        let mut opset = Message::default();
+
+        opset.varint(2, 13);
+
+        let mut model = Message::default();
+
+        model.varint(1, 8);
+        model.bytes(2, b"learning-to-fly");
+        model.message(7, &graph);
+        model.message(8, &opset);
+
+        Ok(model.0)
+    }
+}
+
+impl Normalizer {
+    /// Adds nodes computing `clip((input - offset) * factor, 0, 1)`, returning
+    /// the name of their output.
+    fn export(&self, graph: &mut Message, input: &str) -> String {
+        let (offsets, factors) = self.scales().unzip();
+
+        let offsets = Tensor {
+            name: String::from("normalizer_offsets"),
+            data: offsets,
+        };
+
+        let factors = Tensor {
+            name: String::from("normalizer_factors"),
+            data: factors,
+        };
+
+        let min = Tensor {
+            name: String::from("normalizer_min"),
+            data: vec![0.0],
+        };
+
+        let max = Tensor {
+            name: String::from("normalizer_max"),
+            data: vec![1.0],
+        };
+
+        graph.message(5, &offsets.tensor(&[1, self.len()]));
+        graph.message(5, &factors.tensor(&[1, self.len()]));
+        graph.message(5, &min.tensor(&[]));
+        graph.message(5, &max.tensor(&[]));
+
+        graph.message(
+            1,
+            Node::new("Sub", &[input, &offsets.name], "normalizer_shifted").message(),
+        );
+
+        graph.message(
+            1,
+            Node::new(
+                "Mul",
+                &["normalizer_shifted", &factors.name],
+                "normalizer_scaled",
+            )
+            .message(),
+        );
+
+        graph.message(
+            1,
+            Node::new(
+                "Clip",
+                &["normalizer_scaled", &min.name, &max.name],
+                "normalized",
+            )
+            .message(),
+        );
+
+        String::from("normalized")
+    }
+}
+
+impl DenseLayer {
+    fn weights_tensor(&self, id: usize) -> Tensor {
+        Tensor {
+            name: format!("layer{id}_weights"),
+            data: (0..self.outputs())
+                .flat_map(|n| self.row(n))
+                .copied()
+                .collect(),
+        }
+    }
+
+    fn biases_tensor(&self, id: usize) -> Tensor {
+        Tensor {
+            name: format!("layer{id}_biases"),
+            data: (0..self.outputs()).map(|n| self.bias(n).unwrap()).collect(),
+        }
+    }
+}
+
+struct Tensor {
+    name: String,
+    data: Vec<Float>,
+}
+
+impl Tensor {
+    fn tensor(&self, dims: &[usize]) -> Message {
+        let mut tensor = Message::default();
+
+        for &dim in dims {
+            tensor.varint(1, dim as u64);
+        }
+
+        tensor.varint(2, FLOAT);
+        tensor.bytes(8, self.name.as_bytes());
+
+        let data: Vec<_> = self.data.iter().flat_map(|x| x.to_le_bytes()).collect();
+
+        tensor.bytes(9, &data);
+        tensor
+    }
+}
+
+fn shape_tensor(name: &str, shape: &[usize]) -> Message {
+    let mut tensor = Message::default();
+    let data: Vec<_> = shape
+        .iter()
+        .flat_map(|&x| (x as i64).to_le_bytes())
+        .collect();
+
+    tensor.varint(1, shape.len() as u64);
+    tensor.varint(2, INT64);
+    tensor.bytes(8, name.as_bytes());
+    tensor.bytes(9, &data);
+    tensor
+}
+
+/// Describes a `[1, size]` tensor of [`Float`]s.
+fn value_info(name: &str, size: usize) -> Message {
+    let mut shape = Message::default();
+
+    for dim in [1, size] {
+        let mut dimension = Message::default();
+
+        dimension.varint(1, dim as u64);
+        shape.message(1, &dimension);
+    }
+
+    let mut tensor_type = Message::default();
+
+    tensor_type.varint(1, FLOAT);
+    tensor_type.message(2, &shape);
+
+    let mut ty = Message::default();
+
+    ty.message(1, &tensor_type);
+
+    let mut value_info = Message::default();
+
+    value_info.bytes(1, name.as_bytes());
+    value_info.message(2, &ty);
+    value_info
+}
+
+struct Node(Message);
+
+impl Node {
+    fn new(op: &str, inputs: &[&str], output: &str) -> Self {
+        let mut node = Message::default();
+
+        for input in inputs {
+            node.bytes(1, input.as_bytes());
+        }
+
+        node.bytes(2, output.as_bytes());
+        node.bytes(3, output.as_bytes());
+        node.bytes(4, op.as_bytes());
+
+        Self(node)
+    }
+
+    fn int(mut self, name: &[u8], value: u64) -> Self {
+        let mut attribute = Message::default();
+
+        attribute.bytes(1, name);
+        attribute.varint(3, value);
+        attribute.varint(20, 2);
+
+        self.0.message(5, &attribute);
+        self
+    }
+
+    fn float(mut self, name: &[u8], value: f32) -> Self {
+        let mut attribute = Message::default();
+
+        attribute.bytes(1, name);
+        attribute.fixed32(2, value.to_bits());
+        attribute.varint(20, 1);
+
+        self.0.message(5, &attribute);
+        self
+    }
+
+    fn message(&self) -> &Message {
+        &self.0
+    }
+}
+
+/// Protobuf message being encoded
+#[derive(Default)]
+struct Message(Vec<u8>);
+
+impl Message {
+    fn varint(&mut self, field: u64, value: u64) {
+        self.raw_varint(field << 3);
+        self.raw_varint(value);
+    }
+
+    fn bytes(&mut self, field: u64, value: &[u8]) {
+        self.raw_varint((field << 3) | 2);
+        self.raw_varint(value.len() as u64);
+        self.0.extend_from_slice(value);
+    }
+
+    fn message(&mut self, field: u64, message: &Message) {
+        self.bytes(field, &message.0);
+    }
+
+    fn fixed32(&mut self, field: u64, value: u32) {
+        self.raw_varint((field << 3) | 5);
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+
+        self.0.push(value as u8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes a protobuf message into `(field, value)` pairs, where value is
+    /// either a number (varint or fixed32) or raw bytes.
+    fn decode(mut bytes: &[u8]) -> Vec<(u64, Result<u64, Vec<u8>>)> {
+        fn varint(bytes: &mut &[u8]) -> u64 {
+            let mut value = 0;
+            let mut shift = 0;
+
+            loop {
+                let byte = bytes[0];
+
+                *bytes = &bytes[1..];
+                value |= u64::from(byte & 0x7f) << shift;
+                shift += 7;
+
+                if byte < 0x80 {
+                    return value;
+                }
+            }
+        }
+
+        let mut fields = Vec::new();
+
+        while !bytes.is_empty() {
+            let tag = varint(&mut bytes);
+
+            let value = match tag & 7 {
+                0 => Ok(varint(&mut bytes)),
+
+                5 => {
+                    let (value, rest) = bytes.split_at(4);
+
+                    bytes = rest;
+                    Ok(u64::from(u32::from_le_bytes(value.try_into().unwrap())))
+                }
+
+                2 => {
+                    let len = varint(&mut bytes) as usize;
+                    let (value, rest) = bytes.split_at(len);
+
+                    bytes = rest;
+                    Err(value.to_vec())
+                }
+
+                wire_type => panic!("unexpected wire type: {wire_type}"),
+            };
+
+            fields.push((tag >> 3, value));
+        }
+
+        fields
+    }
+
+    fn field(fields: &[(u64, Result<u64, Vec<u8>>)], field: u64) -> Vec<Vec<u8>> {
+        fields
+            .iter()
+            .filter(|(f, _)| *f == field)
+            .filter_map(|(_, value)| value.clone().err())
+            .collect()
+    }
+
+    fn op_types(network: &Network) -> Vec<String> {
+        let model = decode(&network.to_onnx().unwrap());
+        let graph = decode(&field(&model, 7)[0]);
+
+        field(&graph, 1)
+            .iter()
+            .map(|node| String::from_utf8(field(&decode(node), 4)[0].clone()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn dense() {
+        let network = Network::new(vec![
+            Layer::Dense(DenseLayer::new(
+                vec![0.1, 0.2],
+                vec![vec![0.3, 0.4], vec![0.5, 0.6]],
+                Activation::Relu,
+            )),
+            Layer::Dense(DenseLayer::new(
+                vec![0.7],
+                vec![vec![0.8, 0.9]],
+                Activation::Softmax,
+            )),
+        ]);
+
+        assert_eq!(
+            op_types(&network),
+            ["Gemm", "Relu", "Gemm", "Softmax", "Identity"]
+        );
+
+        let model = decode(&network.to_onnx().unwrap());
+        let graph = decode(&field(&model, 7)[0]);
+        let initializers: Vec<_> = field(&graph, 5).iter().map(|t| decode(t)).collect();
+
+        assert_eq!(
+            field(&initializers[0], 8),
+            [b"layer0_weights".to_vec()],
+            "initializers should be named after their layer"
+        );
+
+        let weights: [Float; 4] = [0.3, 0.4, 0.5, 0.6];
+        let weights: Vec<_> = weights.iter().flat_map(|x| x.to_le_bytes()).collect();
+
+        assert_eq!(field(&initializers[0], 9), [weights]);
+    }
+
+    #[test]
+    fn convolution() {
+        let convolution = Convolution {
+            channels: 1,
+            width: 4,
+            kernel_width: 2,
+            kernel_height: 1,
+            filters: 1,
+        };
+
+        let network = NetworkBuilder::new(4)
+            .convolution(convolution)
+            .output(1, Activation::Sigmoid)
+            .build_from_weights(vec![0.5; 3 + 4]);
+
+        assert_eq!(
+            op_types(&network),
+            ["Reshape", "Conv", "Reshape", "Relu", "Gemm", "Sigmoid", "Identity"]
+        );
+    }
+
+    #[test]
+    fn leaky_relu() {
+        let network = NetworkBuilder::new(2)
+            .layer(2)
+            .activation(Activation::LeakyRelu(0.1))
+            .output(1, Activation::Prelu)
+            .build_from_weights(vec![0.5; 2 * 3 + 3 + 1]);
+
+        assert_eq!(
+            op_types(&network),
+            ["Gemm", "LeakyRelu", "Gemm", "PRelu", "Identity"]
+        );
+
+        let model = decode(&network.to_onnx().unwrap());
+        let graph = decode(&field(&model, 7)[0]);
+        let leaky_relu = decode(&field(&graph, 1)[1]);
+        let alpha = decode(&field(&leaky_relu, 5)[0]);
+
+        assert_eq!(alpha[1], (2, Ok(u64::from(0.1f32.to_bits()))));
+    }
+
+    #[test]
+    fn skip() {
+        let network = NetworkBuilder::new(2)
+            .layer(2)
+            .skip(0)
+            .output(1, Activation::Sigmoid)
+            .build_from_weights(vec![0.5; 2 * 3 + 3]);
+
+        assert_eq!(
+            op_types(&network),
+            ["Gemm", "Add", "Relu", "Gemm", "Sigmoid", "Identity"]
+        );
+
+        let model = decode(&network.to_onnx().unwrap());
+        let graph = decode(&field(&model, 7)[0]);
+        let add = decode(&field(&graph, 1)[1]);
+
+        assert_eq!(
+            field(&add, 1),
+            [b"layer0_linear".to_vec(), b"input".to_vec()]
+        );
+    }
+
+    #[test]
+    fn normalizer() {
+        let mut network = NetworkBuilder::new(2)
+            .layer(2)
+            .skip(0)
+            .build_from_weights(vec![0.5; 2 * 3]);
+
+        network.set_normalizer(Some(Normalizer::new(vec![0.0, -1.0], vec![2.0, 1.0])));
+
+        assert_eq!(
+            op_types(&network),
+            ["Sub", "Mul", "Clip", "Gemm", "Add", "Relu", "Identity"]
+        );
+
+        let model = decode(&network.to_onnx().unwrap());
+        let graph = decode(&field(&model, 7)[0]);
+        let gemm = decode(&field(&graph, 1)[3]);
+        let add = decode(&field(&graph, 1)[4]);
+
+        assert_eq!(field(&gemm, 1)[0], b"normalized");
+        assert_eq!(field(&add, 1)[1], b"normalized");
+    }
+
+    #[test]
+    fn rejects_normalization() {
+        let network = NetworkBuilder::new(2)
+            .layer(2)
+            .normalize(true)
+            .build_from_weights(vec![0.5; 2 * 3]);
+
+        assert_eq!(
+            network.to_onnx(),
+            Err(NetworkError::UnsupportedLayer { layer: 0 })
+        );
+    }
+
+    #[test]
+    fn rejects_recurrent_layers() {
+        let network = NetworkBuilder::new(2)
+            .layer(2)
+            .kind(LayerKind::Recurrent)
+            .build_from_weights(vec![0.5; 2 * 5]);
+
+        assert_eq!(
+            network.to_onnx(),
+            Err(NetworkError::UnsupportedLayer { layer: 0 })
+        );
+    }
+}
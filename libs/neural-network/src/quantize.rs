@@ -0,0 +1,260 @@
+//! Quantized inference, e.g. for simulating large populations on a fraction
+//! of the memory.
+
+use crate::*;
+
+/// Inference-only copy of a [`Network`] with weights stored as `i8`s, see
+/// [`Network::quantize()`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuantizedNetwork {
+    layers: Vec<QuantizedLayer>,
+    skips: Vec<Option<usize>>,
+    normalizer: Option<Normalizer>,
+}
+
+/// Dense layer whose weights are stored as multiples of a per-neuron scale,
+/// chosen so that each neuron's largest weight maps onto `i8::MAX`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct QuantizedLayer {
+    inputs: usize,
+    biases: Vec<Float>,
+    scales: Vec<Float>,
+    weights: Vec<i8>,
+    activation: Activation,
+    prelu_alpha: Float,
+    normalize: bool,
+}
+
+impl Network {
+    /// Converts this network into a [`QuantizedNetwork`], which stores its
+    /// weights in a quarter of the memory (an eighth with the `f64` feature)
+    /// and sums weighted inputs using integer arithmetic, at the cost of some
+    /// precision.
+    ///
+    /// Panics if there are layers other than dense ones, whose state and
+    /// gates aren't worth quantizing.
+    pub fn quantize(&self) -> QuantizedNetwork {
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(id, layer)| match layer {
+                Layer::Dense(layer) => QuantizedLayer::new(layer),
+                _ => panic!("layer #{id} isn't dense, so it can't be quantized"),
+            })
+            .collect();
+
+        QuantizedNetwork {
+            layers,
+            skips: (0..self.layers.len()).map(|id| self.skip(id)).collect(),
+            normalizer: self.normalizer.clone(),
+        }
+    }
+
+    /// Like [`Self::quantize()`], but returns an error instead of panicking
+    /// when there are layers other than dense ones.
+    pub fn try_quantize(&self) -> Result<QuantizedNetwork, NetworkError> {
+        if let Some(layer) = self
+            .layers
+            .iter()
+            .position(|layer| !matches!(layer, Layer::Dense(_)))
+        {
+            return Err(NetworkError::UnsupportedLayer { layer });
+        }
+
+        Ok(self.quantize())
+    }
+}
+
+impl QuantizedNetwork {
+    /// Same as [`Network::propagate()`], except that inputs of each layer get
+    /// quantized to `i8`s as well, so that they can be multiplied with the
+    /// weights and summed up in `i32`s.
+    pub fn propagate(&self, inputs: Vec<Float>) -> Vec<Float> {
+        let mut activations = vec![inputs];
+
+        if let Some(normalizer) = &self.normalizer {
+            normalizer.normalize(&mut activations[0]);
+        }
+
+        for (layer, skip) in self.layers.iter().zip(&self.skips) {
+            let skip = skip.map(|from| activations[from].as_slice());
+            let outputs = layer.propagate(&activations[activations.len() - 1], skip);
+
+            activations.push(outputs);
+        }
+
+        activations.pop().unwrap()
+    }
+}
+
+impl QuantizedLayer {
+    fn new(layer: &DenseLayer) -> Self {
+        let (scales, weights): (Vec<_>, Vec<_>) = (0..layer.outputs())
+            .map(|neuron| quantize(layer.row(neuron)))
+            .map(|(weights, scale)| (scale, weights))
+            .unzip();
+
+        Self {
+            inputs: layer.inputs(),
+            biases: (0..layer.outputs())
+                .map(|neuron| layer.bias(neuron).unwrap())
+                .collect(),
+            scales,
+            weights: weights.concat(),
+            activation: layer.activation(),
+            prelu_alpha: layer.prelu_alpha(),
+            normalize: layer.normalizes(),
+        }
+    }
+
+    /// Propagates `inputs`, adding `skip` (if any) to the weighted sums
+    /// before the activation.
+    fn propagate(&self, inputs: &[Float], skip: Option<&[Float]>) -> Vec<Float> {
+        assert_eq!(inputs.len(), self.inputs);
+
+        let (inputs, input_scale) = quantize(inputs);
+
+        let mut outputs: Vec<_> = (0..self.biases.len())
+            .map(|neuron| {
+                let sum: i32 = self.weights[neuron * self.inputs..][..self.inputs]
+                    .iter()
+                    .zip(&inputs)
+                    .map(|(&weight, &input)| i32::from(weight) * i32::from(input))
+                    .sum();
+
+                self.biases[neuron] + sum as Float * self.scales[neuron] * input_scale
+            })
+            .collect();
+
+        if let Some(skip) = skip {
+            for (output, skip) in outputs.iter_mut().zip(skip) {
+                *output += skip;
+            }
+        }
+
+        if self.normalize {
+            DenseLayer::normalize(&mut outputs);
+        }
+
+        self.activation.apply_with(&mut outputs, self.prelu_alpha);
+        outputs
+    }
+}
+
+/// Maps `values` onto `i8`s, returning them along with the scale to multiply
+/// them by to get the values back.
+fn quantize(values: &[Float]) -> (Vec<i8>, Float) {
+    let scale = values
+        .iter()
+        .copied()
+        .map(Float::abs)
+        .fold(Float::MIN_POSITIVE, Float::max)
+        / Float::from(i8::MAX);
+
+    let values = values.iter().map(|x| (x / scale).round() as i8).collect();
+
+    (values, scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    mod quantize {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let (values, scale) = quantize(&[0.6, -1.0, 0.25, 0.0]);
+
+            assert_eq!(values, [76, -127, 32, 0]);
+            assert_relative_eq!(scale, 1.0 / 127.0);
+        }
+
+        #[test]
+        fn zeros() {
+            let (values, _) = quantize(&[0.0, 0.0]);
+
+            assert_eq!(values, [0, 0]);
+        }
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let network = NetworkBuilder::new(8)
+                .layer(16)
+                .activation(Activation::Tanh)
+                .output(4, Activation::Sigmoid)
+                .build(&mut rng);
+
+            let quantized = network.quantize();
+            let inputs: Vec<_> = (0..8).map(|i| i as Float / 8.0 - 0.5).collect();
+
+            let expected = network.propagate(inputs.clone());
+            let actual = quantized.propagate(inputs);
+
+            assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 0.02);
+        }
+
+        #[test]
+        fn biases_are_exact() {
+            let network = Network::new(vec![Layer::Dense(DenseLayer::new(
+                vec![0.1, -0.2],
+                vec![vec![0.5, -1.0], vec![0.25, 0.0]],
+                Activation::Linear,
+            ))]);
+
+            let actual = network.quantize().propagate(vec![0.0, 0.0]);
+
+            assert_eq!(actual, [0.1, -0.2]);
+        }
+    }
+
+    mod skip {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let network = NetworkBuilder::new(2)
+                .layer(2)
+                .skip(0)
+                .output(2, Activation::Linear)
+                .skip(0)
+                .build_from_weights([[0.0; 6], [0.0, 1.0, 0.0, 0.0, 0.0, 1.0]].concat());
+
+            let expected = network.propagate(vec![1.0, -1.0]);
+            let actual = network.quantize().propagate(vec![1.0, -1.0]);
+
+            assert_relative_eq!(expected.as_slice(), [2.0, -1.0].as_ref());
+            assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 1e-6);
+        }
+    }
+
+    mod try_quantize {
+        use super::*;
+
+        #[test]
+        fn rejects_recurrent_layers() {
+            let network = NetworkBuilder::new(1)
+                .layer(1)
+                .kind(LayerKind::Recurrent)
+                .build_from_weights([0.0; 3]);
+
+            assert_eq!(
+                network.try_quantize().unwrap_err(),
+                NetworkError::UnsupportedLayer { layer: 0 }
+            );
+        }
+    }
+}
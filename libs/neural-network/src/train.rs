@@ -0,0 +1,370 @@
+//! Supervised training with backpropagation, e.g. to pre-train brains on
+//! recorded play before the genetic algorithm takes over.
+
+use crate::*;
+
+impl Network {
+    /// Trains this network with stochastic gradient descent on the mean
+    /// squared error, going through `samples` (pairs of inputs and expected
+    /// outputs) in order, `epochs` times; returns the mean loss of the last
+    /// epoch.
+    ///
+    /// Only dense layers are supported (skip connections included), with any
+    /// activation but [`Activation::Prelu`] and without normalization.
+    /// Dropout is applied only in training mode (see
+    /// [`Self::enable_training()`]).
+    pub fn fit(
+        &mut self,
+        samples: &[(Vec<Float>, Vec<Float>)],
+        epochs: usize,
+        learning_rate: Float,
+    ) -> Result<Float, NetworkError> {
+        if let Some(layer) = self.layers.iter().position(|layer| {
+            !matches!(layer, Layer::Dense(_))
+                || layer.params().activation() == Activation::Prelu
+                || layer.params().normalizes()
+        }) {
+            return Err(NetworkError::UnsupportedLayer { layer });
+        }
+
+        for (inputs, outputs) in samples {
+            if inputs.len() != self.input_size() {
+                return Err(NetworkError::InputSizeMismatch {
+                    expected: self.input_size(),
+                    actual: inputs.len(),
+                });
+            }
+
+            if outputs.len() != self.output_size() {
+                return Err(NetworkError::OutputSizeMismatch {
+                    expected: self.output_size(),
+                    actual: outputs.len(),
+                });
+            }
+        }
+
+        let mut loss = 0.0;
+
+        // Copies of the network's RNGs, put back once the training's done
+        let mut rngs = Rngs::default();
+
+        for _ in 0..epochs {
+            loss = samples
+                .iter()
+                .map(|(inputs, expected)| {
+                    self.fit_sample(&mut rngs, inputs, expected, learning_rate)
+                })
+                .sum::<Float>()
+                / samples.len().max(1) as Float;
+        }
+
+        if let Some(rng) = rngs.dropout {
+            self.training = Some(rng);
+        }
+
+        if let (Some(noise), Some(rng)) = (&mut self.noise, rngs.noise) {
+            noise.rng = rng;
+        }
+
+        Ok(loss)
+    }
+
+    /// Performs a step of gradient descent on a single sample, returning its
+    /// loss from before the step.
+    fn fit_sample(
+        &mut self,
+        rngs: &mut Rngs,
+        inputs: &[Float],
+        expected: &[Float],
+        learning_rate: Float,
+    ) -> Float {
+        // Inputs of each layer (after the previous layer's dropout), followed
+        // by the network's outputs
+        let mut activations = vec![inputs.to_vec()];
+
+        self.preprocess(Some(rngs), &mut activations[0]);
+
+        // Outputs of each layer before dropout, along with the dropout's mask
+        let mut outputs = Vec::with_capacity(self.layers.len());
+
+        for (id, layer) in self.layers.iter().enumerate() {
+            let mut layer_outputs = Vec::with_capacity(layer.outputs());
+            let skip = self.skip(id).map(|from| activations[from].as_slice());
+
+            self.propagate_layer(None, id, &activations[id], skip, &mut layer_outputs);
+
+            let mask = self.dropout_mask(&mut rngs.dropout, id, layer_outputs.len());
+
+            activations.push(match &mask {
+                Some(mask) => layer_outputs.iter().zip(mask).map(|(y, m)| y * m).collect(),
+                None => layer_outputs.clone(),
+            });
+
+            outputs.push((layer_outputs, mask));
+        }
+
+        let len = expected.len() as Float;
+        let network_outputs = &activations[activations.len() - 1];
+
+        let loss = network_outputs
+            .iter()
+            .zip(expected)
+            .map(|(y, t)| (y - t) * (y - t))
+            .sum::<Float>()
+            / len;
+
+        let mut grads: Vec<_> = network_outputs
+            .iter()
+            .zip(expected)
+            .map(|(y, t)| 2.0 * (y - t) / len)
+            .collect();
+
+        // Gradients with respect to each layer's outputs which come through
+        // skip connections, indexed like `activations`
+        let mut skip_grads: Vec<Option<Vec<Float>>> = vec![None; activations.len()];
+
+        for id in (0..self.layers.len()).rev() {
+            let (outputs, mask) = &outputs[id];
+
+            if let Some(skip_grads) = skip_grads[id + 1].take() {
+                for (grad, skip_grad) in grads.iter_mut().zip(skip_grads) {
+                    *grad += skip_grad;
+                }
+            }
+
+            if let Some(mask) = mask {
+                for (grad, m) in grads.iter_mut().zip(mask) {
+                    *grad *= m;
+                }
+            }
+
+            let skip = self.skip(id);
+            let params = self.layers[id].params_mut();
+
+            params.activation().backpropagate(outputs, &mut grads);
+
+            if let Some(from) = skip {
+                let skip_grads = skip_grads[from].get_or_insert_with(|| vec![0.0; grads.len()]);
+
+                for (skip_grad, grad) in skip_grads.iter_mut().zip(&grads) {
+                    *skip_grad += grad;
+                }
+            }
+
+            grads = params.backpropagate(&activations[id], grads, learning_rate);
+        }
+
+        loss
+    }
+}
+
+impl Network {
+    /// Draws factors that given layer's outputs get multiplied by in training
+    /// mode (see `Network::dropout()`), or returns `None` if there's no
+    /// dropout.
+    fn dropout_mask(
+        &self,
+        rng: &mut Option<StdRng>,
+        layer: usize,
+        len: usize,
+    ) -> Option<Vec<Float>> {
+        let rate = self.dropout_rate(layer);
+        let training = self.training.as_ref().filter(|_| rate > 0.0)?;
+        let rng = rng.get_or_insert_with(|| training.clone());
+        let scale = Float::from(1.0 / (1.0 - rate));
+
+        Some(
+            (0..len)
+                .map(|_| if rng.gen_bool(rate as _) { 0.0 } else { scale })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn learns_linear_function() {
+        let mut network = NetworkBuilder::new(1)
+            .output(1, Activation::Linear)
+            .build_from_weights([0.0, 0.0]);
+
+        let samples: Vec<_> = [-1.0, 0.0, 1.0, 2.0]
+            .into_iter()
+            .map(|x| (vec![x], vec![2.0 * x - 1.0]))
+            .collect();
+
+        let loss = network.fit(&samples, 500, 0.05).unwrap();
+
+        assert!(loss < 1e-6, "loss = {loss}");
+        assert!((network.weight(0, 0, 0).unwrap() - 2.0).abs() < 1e-3);
+        assert!((network.bias(0, 0).unwrap() + 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn learns_xor() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let mut network = NetworkBuilder::new(2)
+            .layer(4)
+            .activation(Activation::Tanh)
+            .output(1, Activation::Sigmoid)
+            .build(&mut rng);
+
+        let samples = vec![
+            (vec![0.0, 0.0], vec![0.0]),
+            (vec![0.0, 1.0], vec![1.0]),
+            (vec![1.0, 0.0], vec![1.0]),
+            (vec![1.0, 1.0], vec![0.0]),
+        ];
+
+        let loss = network.fit(&samples, 2000, 0.5).unwrap();
+
+        assert!(loss < 0.01, "loss = {loss}");
+
+        for (inputs, expected) in samples {
+            let actual = network.propagate(inputs)[0];
+
+            assert!((actual - expected[0]).abs() < 0.2);
+        }
+    }
+
+    #[test]
+    fn keeps_masked_weights_zero() {
+        let mut network = NetworkBuilder::new(2)
+            .output(1, Activation::Linear)
+            .mask(vec![true, false])
+            .build_from_weights([0.0, 0.0]);
+
+        let samples: Vec<_> = [-1.0, 0.0, 1.0, 2.0]
+            .into_iter()
+            .map(|x| (vec![x, x], vec![2.0 * x]))
+            .collect();
+
+        let loss = network.fit(&samples, 500, 0.05).unwrap();
+
+        assert!(loss < 1e-6, "loss = {loss}");
+        assert_eq!(network.weight(0, 0, 1), Some(0.0));
+    }
+
+    #[test]
+    fn learns_through_skip_connections() {
+        let mut network = NetworkBuilder::new(1)
+            .layer(1)
+            .activation(Activation::Linear)
+            .output(1, Activation::Linear)
+            .skip(0)
+            .build_from_weights([0.0, 0.5, 0.0, 0.5]);
+
+        // y = x + w2 * (w1 * x + b1) + b2
+        let samples: Vec<_> = [-1.0, 0.0, 1.0, 2.0]
+            .into_iter()
+            .map(|x| (vec![x], vec![2.0 * x + 1.0]))
+            .collect();
+
+        let loss = network.fit(&samples, 1000, 0.05).unwrap();
+
+        assert!(loss < 1e-6, "loss = {loss}");
+
+        let w1 = network.weight(0, 0, 0).unwrap();
+        let w2 = network.weight(1, 0, 0).unwrap();
+
+        assert!((w1 * w2 - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn applies_dropout_in_training_mode() {
+        let samples = vec![(vec![1.0, 1.0], vec![1.0])];
+
+        let network = || {
+            NetworkBuilder::new(2)
+                .layer(100)
+                .dropout(0.5)
+                .output(1, Activation::Linear)
+                .build_from_weights([0.5; 300 + 101])
+        };
+
+        let mut without_dropout = network();
+        let mut with_dropout = network();
+
+        with_dropout.enable_training(0);
+
+        let loss_without_dropout = without_dropout.fit(&samples, 1, 1e-4).unwrap();
+        let loss_with_dropout = with_dropout.fit(&samples, 1, 1e-4).unwrap();
+
+        assert_ne!(loss_with_dropout, loss_without_dropout);
+
+        // Dropped neurons get no gradient, so their weights don't change
+        let unchanged = (0..100)
+            .filter(|&neuron| with_dropout.bias(0, neuron) == Some(0.5))
+            .count();
+
+        assert!((30..70).contains(&unchanged), "unchanged = {unchanged}");
+        assert!((0..100).all(|neuron| without_dropout.bias(0, neuron) != Some(0.5)));
+    }
+
+    #[test]
+    fn rejects_recurrent_layers() {
+        let mut network = NetworkBuilder::new(1)
+            .layer(1)
+            .kind(LayerKind::Recurrent)
+            .build_from_weights([0.0; 3]);
+
+        assert_eq!(
+            network.fit(&[], 1, 0.1),
+            Err(NetworkError::UnsupportedLayer { layer: 0 })
+        );
+    }
+
+    #[test]
+    fn rejects_prelu() {
+        let mut network = NetworkBuilder::new(1)
+            .output(1, Activation::Prelu)
+            .build_from_weights([0.0; 3]);
+
+        assert_eq!(
+            network.fit(&[], 1, 0.1),
+            Err(NetworkError::UnsupportedLayer { layer: 0 })
+        );
+    }
+
+    #[test]
+    fn rejects_normalization() {
+        let mut network = NetworkBuilder::new(1)
+            .layer(2)
+            .normalize(true)
+            .build_from_weights([0.0; 4]);
+
+        assert_eq!(
+            network.fit(&[], 1, 0.1),
+            Err(NetworkError::UnsupportedLayer { layer: 0 })
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_sample_sizes() {
+        let mut network = NetworkBuilder::new(2)
+            .output(1, Activation::Linear)
+            .build_from_weights([0.0; 3]);
+
+        assert_eq!(
+            network.fit(&[(vec![1.0], vec![1.0])], 1, 0.1),
+            Err(NetworkError::InputSizeMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+
+        assert_eq!(
+            network.fit(&[(vec![1.0, 2.0], vec![])], 1, 0.1),
+            Err(NetworkError::OutputSizeMismatch {
+                expected: 1,
+                actual: 0
+            })
+        );
+    }
+}